@@ -0,0 +1,85 @@
+//! Benchmarks of key generation, encapsulation and decapsulation.
+//!
+//! It requires OpenSSL 3.0 or later, and oqsprovider to be installed. Each benchmark is compared against the
+//! [rustls-post-quantum] implementation of the same group, so overhead from oqsprovider is visible.
+//!
+//! [rustls-post-quantum]: https://docs.rs/rustls-post-quantum
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use rustls::crypto::SupportedKxGroup;
+use rustls_liboqs::OqsProviderGuard;
+
+/// The groups to benchmark, each paired with its rustls-post-quantum counterpart.
+const GROUPS: &[(&str, &dyn SupportedKxGroup, &dyn SupportedKxGroup)] = &[
+    (
+        "MLKEM768",
+        rustls_liboqs::MLKEM768,
+        rustls_post_quantum::MLKEM768,
+    ),
+    (
+        "X25519MLKEM768",
+        rustls_liboqs::X25519MLKEM768,
+        rustls_post_quantum::X25519MLKEM768,
+    ),
+];
+
+fn start(c: &mut Criterion) {
+    let mut group = c.benchmark_group("start");
+    for (name, liboqs, post_quantum) in GROUPS {
+        group.bench_function(BenchmarkId::new("rustls-liboqs", name), |b| {
+            b.iter(|| liboqs.start().unwrap())
+        });
+        group.bench_function(BenchmarkId::new("rustls-post-quantum", name), |b| {
+            b.iter(|| post_quantum.start().unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn start_and_complete(c: &mut Criterion) {
+    let mut group = c.benchmark_group("start_and_complete");
+    for (name, liboqs, post_quantum) in GROUPS {
+        for (implementation, kx_group) in [
+            ("rustls-liboqs", liboqs),
+            ("rustls-post-quantum", post_quantum),
+        ] {
+            let pub_key = kx_group.start().unwrap().pub_key().to_vec();
+            group.bench_function(BenchmarkId::new(implementation, name), |b| {
+                b.iter(|| kx_group.start_and_complete(&pub_key).unwrap())
+            });
+        }
+    }
+    group.finish();
+}
+
+fn complete(c: &mut Criterion) {
+    let mut group = c.benchmark_group("complete");
+    // Only MLKEM768 is compared, as the hybrid decapsulation cost is dominated by the same ML-KEM operation.
+    let (name, liboqs, post_quantum) = GROUPS[0];
+    for (implementation, kx_group) in [
+        ("rustls-liboqs", liboqs),
+        ("rustls-post-quantum", post_quantum),
+    ] {
+        group.bench_function(BenchmarkId::new(implementation, name), |b| {
+            b.iter_batched(
+                || {
+                    let kx = kx_group.start().unwrap();
+                    let ciphertext = kx_group.start_and_complete(kx.pub_key()).unwrap().pub_key;
+                    (kx, ciphertext)
+                },
+                |(kx, ciphertext)| kx.complete(&ciphertext).unwrap(),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn benches(c: &mut Criterion) {
+    let _providers = OqsProviderGuard::load().expect("Failed to load OQS provider.");
+    start(c);
+    start_and_complete(c);
+    complete(c);
+}
+
+criterion_group!(kem, benches);
+criterion_main!(kem);
@@ -0,0 +1,123 @@
+//! Compares the allocating and in-place KEM operations.
+//!
+//! It requires OpenSSL 3.0 or later, and oqsprovider to be installed. Run it with `cargo bench --bench kem_buffers`.
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use openssl::pkey::{PKey, Private, Public};
+use openssl::pkey_ctx::PkeyCtx;
+use rustls_liboqs::ffi::{PKeyRefExt, PkeyCtxExt, PkeyCtxRefKemExt, PkeyExt};
+use rustls_liboqs::OqsProviderGuard;
+use std::hint::black_box;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Generates an ML-KEM-768 key pair.
+fn key_pair() -> (PKey<Private>, PKey<Public>) {
+    let mut keygen = PkeyCtx::<()>::new_from_name(b"mlkem768\0").unwrap();
+    keygen.keygen_init().unwrap();
+    let priv_key = keygen.keygen().unwrap();
+    let pub_key = PKey::from_encoded_public_key(
+        &priv_key
+            .get_octet_string_param(b"encoded-pub-key\0")
+            .unwrap(),
+        b"mlkem768\0",
+    )
+    .unwrap();
+    (priv_key, pub_key)
+}
+
+fn decapsulation_ctx(priv_key: &PKey<Private>) -> PkeyCtx<Private> {
+    let ctx = PkeyCtx::new(priv_key).unwrap();
+    ctx.decapsulate_init().unwrap();
+    ctx
+}
+
+/// Runs `f` `iters` times on each of `threads` threads and returns the time taken per `iters` calls.
+///
+/// Each thread calls `setup` once and passes its result to `f`. Allocations contend on the allocator under
+/// concurrent load, so this shows their cost better than a single thread.
+fn time_concurrent<T>(
+    iters: u64,
+    threads: u32,
+    setup: impl Fn() -> T + Sync,
+    f: impl Fn(&T) + Sync,
+) -> Duration {
+    let start = Instant::now();
+    thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| {
+                let state = setup();
+                for _ in 0..iters {
+                    f(&state);
+                }
+            });
+        }
+    });
+    start.elapsed() / threads
+}
+
+fn benches(c: &mut Criterion) {
+    let _providers = OqsProviderGuard::load().expect("Failed to load OQS provider.");
+    let (priv_key, pub_key) = key_pair();
+
+    let mut ctx = PkeyCtx::new(&pub_key).unwrap();
+    ctx.encapsulate_init().unwrap();
+    let mut group = c.benchmark_group("mlkem768_encapsulate");
+    group.bench_function("to_vec", |b| b.iter(|| ctx.encapsulate_to_vec().unwrap()));
+    group.bench_function("in_place", |b| {
+        let mut out = [0; 1088];
+        let mut secret = [0; 32];
+        b.iter(|| {
+            ctx.encapsulate_in_place(&mut out, &mut secret).unwrap();
+            black_box((&out, &secret));
+        })
+    });
+    group.finish();
+
+    let (ciphertext, _) = ctx.encapsulate_to_vec().unwrap();
+    let ctx = decapsulation_ctx(&priv_key);
+    let mut group = c.benchmark_group("mlkem768_decapsulate");
+    group.bench_function("to_vec", |b| {
+        b.iter(|| ctx.decapsulate_to_vec(&ciphertext).unwrap())
+    });
+    group.bench_function("in_place", |b| {
+        b.iter(|| {
+            let mut secret = [0; 32];
+            ctx.decapsulate_in_place(&ciphertext, &mut secret).unwrap();
+            secret
+        })
+    });
+    group.finish();
+
+    let threads = thread::available_parallelism().map_or(4, |n| n.get() as u32);
+    // Each thread needs its own context, as OpenSSL contexts aren't thread safe
+    let mut group = c.benchmark_group("mlkem768_decapsulate_concurrent");
+    group.bench_function(BenchmarkId::new("to_vec", threads), |b| {
+        b.iter_custom(|iters| {
+            time_concurrent(
+                iters,
+                threads,
+                || decapsulation_ctx(&priv_key),
+                |ctx| {
+                    black_box(ctx.decapsulate_to_vec(&ciphertext).unwrap());
+                },
+            )
+        })
+    });
+    group.bench_function(BenchmarkId::new("in_place", threads), |b| {
+        b.iter_custom(|iters| {
+            time_concurrent(
+                iters,
+                threads,
+                || decapsulation_ctx(&priv_key),
+                |ctx| {
+                    let mut secret = [0; 32];
+                    black_box(ctx.decapsulate_in_place(&ciphertext, &mut secret).unwrap());
+                },
+            )
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(kem_buffers, benches);
+criterion_main!(kem_buffers);
@@ -0,0 +1,41 @@
+//! Compares the throughput of key exchanges with and without a [`KxGroupPool`].
+//!
+//! It requires OpenSSL 3.0 or later, and oqsprovider to be installed. Each iteration is the client side of a
+//! handshake: starting a key exchange, then completing it with a ciphertext encapsulated to its public key. With a
+//! pool, key generation happens on the pool's background thread instead, so the gain needs a spare CPU core.
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rustls::crypto::{ActiveKeyExchange, SupportedKxGroup};
+use rustls_liboqs::{KxGroupPool, OqsProviderGuard};
+
+/// The groups to benchmark.
+const GROUPS: &[(&str, &dyn SupportedKxGroup)] = &[
+    ("MLKEM768", rustls_liboqs::MLKEM768),
+    ("X25519MLKEM768", rustls_liboqs::X25519MLKEM768),
+];
+
+const POOL_CAPACITY: usize = 64;
+
+/// Completes a key exchange with a ciphertext encapsulated to its public key.
+fn handshake(group: &dyn SupportedKxGroup, kx: Box<dyn ActiveKeyExchange>) {
+    let ciphertext = group.start_and_complete(kx.pub_key()).unwrap().pub_key;
+    kx.complete(&ciphertext).unwrap();
+}
+
+fn benches(c: &mut Criterion) {
+    let _providers = OqsProviderGuard::load().expect("Failed to load OQS provider.");
+    let mut bench_group = c.benchmark_group("handshake");
+    bench_group.throughput(Throughput::Elements(1));
+    for (name, group) in GROUPS {
+        bench_group.bench_function(BenchmarkId::new("direct", name), |b| {
+            b.iter(|| handshake(*group, group.start().unwrap()))
+        });
+        let pool = KxGroupPool::new(*group, POOL_CAPACITY);
+        bench_group.bench_function(BenchmarkId::new("pooled", name), |b| {
+            b.iter(|| handshake(*group, pool.take().unwrap()))
+        });
+    }
+    bench_group.finish();
+}
+
+criterion_group!(pool, benches);
+criterion_main!(pool);
@@ -0,0 +1,145 @@
+#[cfg(feature = "bundled")]
+#[path = "build/bundled.rs"]
+mod bundled;
+#[path = "src/version.rs"]
+#[allow(dead_code)]
+mod oqs_version;
+#[path = "build/version.rs"]
+#[cfg_attr(feature = "bundled", allow(dead_code))]
+mod version;
+
+use oqs_version::OqsProviderVersion;
+
+/// oqs-provider 0.7.0 is the first version that doesn't use the legacy hybrid key encoding, see `swapx25519hybrid`.
+const FIXED_OQS_PROVIDER_VERSION: OqsProviderVersion = OqsProviderVersion::new(0, 7, 0);
+
+/// The oldest oqs-provider version that this crate has been tested with.
+const MINIMUM_OQS_PROVIDER_VERSION: OqsProviderVersion = OqsProviderVersion::new(0, 6, 0);
+
+/// liboqs 0.12 is the first version whose oqs-provider releases (0.7.0 and later) don't use the legacy hybrid key
+/// encoding.
+#[cfg(not(feature = "bundled"))]
+const FIXED_LIBOQS_VERSION: OqsProviderVersion = OqsProviderVersion::new(0, 12, 0);
+
+/// The liboqs version used by the oldest tested oqs-provider version.
+#[cfg(not(feature = "bundled"))]
+const MINIMUM_LIBOQS_VERSION: OqsProviderVersion = OqsProviderVersion::new(0, 10, 0);
+
+/// The version thresholds for a package.
+struct Thresholds {
+    /// Versions older than this are untested.
+    minimum: OqsProviderVersion,
+    /// Versions older than this use the legacy hybrid key encoding, and have known interoperability issues.
+    fixed: OqsProviderVersion,
+}
+
+/// Returns the version of a pkg-config package, without emitting link directives.
+///
+/// oqsprovider is loaded at runtime, so we don't link against it or liboqs.
+#[cfg(not(feature = "bundled"))]
+fn probe_version(package: &str) -> Option<String> {
+    pkg_config::Config::new()
+        .cargo_metadata(false)
+        .probe(package)
+        .ok()
+        .map(|library| library.version)
+}
+
+/// Returns the version of a vcpkg installation of liboqs, without emitting link directives.
+///
+/// pkg-config is rarely available on Windows, where native dependencies are usually managed by vcpkg instead. The
+/// vcpkg port doesn't ship a pkg-config file, so the version is read from the installed `oqs/oqsconfig.h` header.
+#[cfg(not(feature = "bundled"))]
+fn probe_vcpkg_version() -> Option<String> {
+    if std::env::var("CARGO_CFG_TARGET_OS").as_deref() != Ok("windows") {
+        return None;
+    }
+    let library = vcpkg::Config::new()
+        .cargo_metadata(false)
+        .find_package("liboqs")
+        .ok()?;
+    library.include_paths.iter().find_map(|include| {
+        let header = std::fs::read_to_string(include.join("oqs").join("oqsconfig.h")).ok()?;
+        version::parse_oqs_version_text(&header).map(str::to_owned)
+    })
+}
+
+/// Returns the installed oqs-provider version, or the liboqs version if oqs-provider's isn't available.
+#[cfg(not(feature = "bundled"))]
+fn installed_version() -> Option<(&'static str, String, Thresholds)> {
+    if let Some(version) = probe_version("oqs-provider") {
+        return Some((
+            "oqs-provider",
+            version,
+            Thresholds {
+                minimum: MINIMUM_OQS_PROVIDER_VERSION,
+                fixed: FIXED_OQS_PROVIDER_VERSION,
+            },
+        ));
+    }
+    println!(
+        "cargo:warning=oqs-provider pkg-config not found, falling back to liboqs version as proxy"
+    );
+    let version = probe_version("liboqs").or_else(probe_vcpkg_version)?;
+    Some((
+        "liboqs",
+        version,
+        Thresholds {
+            minimum: MINIMUM_LIBOQS_VERSION,
+            fixed: FIXED_LIBOQS_VERSION,
+        },
+    ))
+}
+
+/// Builds liboqs and oqs-provider from source, returning the pinned oqs-provider version.
+#[cfg(feature = "bundled")]
+fn installed_version() -> Option<(&'static str, String, Thresholds)> {
+    println!("cargo:rerun-if-changed=build/bundled.rs");
+    bundled::build();
+    Some((
+        "oqs-provider",
+        bundled::OQS_PROVIDER_VERSION.to_string(),
+        Thresholds {
+            minimum: MINIMUM_OQS_PROVIDER_VERSION,
+            fixed: FIXED_OQS_PROVIDER_VERSION,
+        },
+    ))
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=build/version.rs");
+    println!("cargo:rerun-if-changed=src/version.rs");
+    println!("cargo:rerun-if-env-changed=OQS_PROVIDER_PATH");
+    println!("cargo:rerun-if-env-changed=PKG_CONFIG_PATH");
+    println!("cargo:rerun-if-env-changed=VCPKG_ROOT");
+
+    let Some((package, version, thresholds)) = installed_version() else {
+        return;
+    };
+
+    let Ok(parsed) = version.parse::<OqsProviderVersion>() else {
+        println!(
+            "cargo:warning=could not parse {package} version {version:?}, assuming oqs-provider 0.7.0 or later"
+        );
+        return;
+    };
+
+    if parsed < thresholds.minimum {
+        let minimum = thresholds.minimum;
+        println!(
+            "cargo:warning={package} version {version} is older than the minimum tested version {minimum}; consider upgrading to oqs-provider 0.7.0 or later"
+        );
+    } else if parsed < thresholds.fixed {
+        println!(
+            "cargo:warning={package} version {parsed} may have known interoperability issues; consider upgrading to oqs-provider 0.7.0 or later"
+        );
+    }
+
+    // The legacy hybrid key encoding places the classical component of X25519MLKEM768 first, and prefixes
+    // hybrid public keys read from the provider with their length. See `ReversingKeyExchange` and
+    // `KxGroup::pub_key_has_length_prefix`.
+    if parsed < thresholds.fixed {
+        println!("cargo:rustc-cfg=swapx25519hybrid");
+    }
+}
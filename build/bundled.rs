@@ -0,0 +1,74 @@
+//! Builds liboqs and oqs-provider from source for the `bundled` feature.
+//!
+//! Both are built as static libraries and linked into the crate, and oqs-provider is registered as a built-in
+//! provider at runtime, so neither needs to be installed on the target system.
+use std::env;
+use std::path::{Path, PathBuf};
+
+/// The liboqs release expected in `vendor/liboqs`.
+pub const LIBOQS_VERSION: &str = "0.12.0";
+
+/// The oqs-provider release expected in `vendor/oqs-provider`.
+pub const OQS_PROVIDER_VERSION: &str = "0.8.0";
+
+/// Returns the source directory from `var` if set, or the vendored checkout otherwise.
+fn source_dir(var: &str, vendored: &str, version: &str) -> PathBuf {
+    println!("cargo:rerun-if-env-changed={var}");
+    let dir = env::var_os(var)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| Path::new(env!("CARGO_MANIFEST_DIR")).join(vendored));
+    if !dir.join("CMakeLists.txt").exists() {
+        panic!(
+            "the bundled feature needs the {vendored} sources in {}; check out release {version} there or set {var}",
+            dir.display()
+        );
+    }
+    dir
+}
+
+/// Builds and links liboqs and oqs-provider.
+pub fn build() {
+    let liboqs_src = source_dir("LIBOQS_SRC_DIR", "vendor/liboqs", LIBOQS_VERSION);
+    let provider_src = source_dir(
+        "OQS_PROVIDER_SRC_DIR",
+        "vendor/oqs-provider",
+        OQS_PROVIDER_VERSION,
+    );
+
+    // openssl-sys exports its include directory to crates depending on it
+    let openssl_root = env::var_os("DEP_OPENSSL_INCLUDE")
+        .map(PathBuf::from)
+        .and_then(|include| include.parent().map(Path::to_path_buf));
+
+    let liboqs = cmake::Config::new(&liboqs_src)
+        .define("BUILD_SHARED_LIBS", "OFF")
+        .define("OQS_BUILD_ONLY_LIB", "ON")
+        .define("OQS_USE_OPENSSL", "ON")
+        .build();
+
+    let mut provider = cmake::Config::new(&provider_src);
+    provider
+        .define("OQS_PROVIDER_BUILD_STATIC", "ON")
+        .define(
+            "liboqs_DIR",
+            liboqs.join("lib").join("cmake").join("liboqs"),
+        )
+        .build_target("oqsprovider");
+    if let Some(openssl_root) = &openssl_root {
+        provider.define("OPENSSL_ROOT_DIR", openssl_root);
+    }
+    let provider = provider.build();
+
+    println!(
+        "cargo:rustc-link-search=native={}",
+        provider.join("build").join("lib").display()
+    );
+    println!("cargo:rustc-link-lib=static=oqsprovider");
+    for lib in ["lib", "lib64"] {
+        println!(
+            "cargo:rustc-link-search=native={}",
+            liboqs.join(lib).display()
+        );
+    }
+    println!("cargo:rustc-link-lib=static=oqs");
+}
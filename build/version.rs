@@ -0,0 +1,11 @@
+/// Extracts the version from the `OQS_VERSION_TEXT` define in liboqs' `oqs/oqsconfig.h` header.
+pub fn parse_oqs_version_text(header: &str) -> Option<&str> {
+    header.lines().find_map(|line| {
+        let value = line
+            .trim()
+            .strip_prefix("#define")?
+            .trim_start()
+            .strip_prefix("OQS_VERSION_TEXT")?;
+        value.trim().strip_prefix('"')?.strip_suffix('"')
+    })
+}
@@ -0,0 +1,89 @@
+//! An async TLS client using tokio-rustls, preferring the X25519MLKEM768 key exchange.
+//!
+//! It requires OpenSSL 3.0 or later, and oqsprovider to be installed.
+//!
+//! By default it connects to the async server example on `localhost:4443`, trusting the certificate the server
+//! wrote to `$TMPDIR/rustls-liboqs-async-server.pem`. Pass a host and port to connect elsewhere, e.g
+//! `pq.cloudflareresearch.com 443`, in which case the webpki roots are trusted.
+//!
+//! The client sends a HTTP request and prints the response, which the async server example echoes back. Classical
+//! groups are also offered, so the handshake succeeds with servers that don't support X25519MLKEM768; a warning is
+//! printed if a classical group is negotiated.
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, ServerName};
+use rustls::{ClientConfig, NamedGroup, RootCertStore};
+use rustls_liboqs::{OqsProviderGuard, X25519MLKEM768};
+use std::error::Error;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{stdout, AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+use tokio_rustls::TlsConnector;
+
+/// How long to wait for each of connecting, the handshake and the response.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+fn root_store() -> Result<RootCertStore, Box<dyn Error>> {
+    let mut roots = RootCertStore {
+        roots: webpki_roots::TLS_SERVER_ROOTS.into(),
+    };
+    let local_cert = std::env::temp_dir().join("rustls-liboqs-async-server.pem");
+    if local_cert.exists() {
+        roots.add(CertificateDer::from_pem_file(local_cert)?)?;
+    }
+    Ok(roots)
+}
+
+fn client_config() -> Result<ClientConfig, Box<dyn Error>> {
+    // Prefer X25519MLKEM768, falling back to classical groups for servers without post-quantum support
+    let mut kx_groups = vec![X25519MLKEM768];
+    kx_groups.extend(rustls_openssl::ALL_KX_GROUPS);
+    let provider =
+        rustls_openssl::custom_provider(rustls_openssl::ALL_CIPHER_SUITES.to_vec(), kx_groups);
+
+    Ok(ClientConfig::builder_with_provider(Arc::new(provider))
+        .with_safe_default_protocol_versions()?
+        .with_root_certificates(root_store()?)
+        .with_no_client_auth())
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let _providers = OqsProviderGuard::load()?;
+    env_logger::init();
+
+    let mut args = std::env::args().skip(1);
+    let host = args.next().unwrap_or_else(|| "localhost".to_string());
+    let port: u16 = args.next().map_or(Ok(4443), |port| port.parse())?;
+
+    let connector = TlsConnector::from(Arc::new(client_config()?));
+    let server_name = ServerName::try_from(host.clone())?;
+
+    let stream = timeout(TIMEOUT, TcpStream::connect((host.as_str(), port))).await??;
+    let mut stream = timeout(TIMEOUT, connector.connect(server_name, stream)).await??;
+
+    let group = stream
+        .get_ref()
+        .1
+        .negotiated_key_exchange_group()
+        .map(|group| group.name());
+    eprintln!("Negotiated key exchange group: {group:?}");
+    if group != Some(NamedGroup::X25519MLKEM768) {
+        eprintln!(
+            "Warning: the server doesn't support X25519MLKEM768, so a classical group was used"
+        );
+    }
+
+    let request = format!(
+        "GET /cdn-cgi/trace HTTP/1.0\r\nHost: {host}\r\nConnection: close\r\nAccept-Encoding: identity\r\n\r\n"
+    );
+    stream.write_all(request.as_bytes()).await?;
+    // Close our side, so the echo server knows the request is complete
+    stream.shutdown().await?;
+
+    let mut response = Vec::new();
+    timeout(TIMEOUT, stream.read_to_end(&mut response)).await??;
+    stdout().write_all(&response).await?;
+    Ok(())
+}
@@ -0,0 +1,89 @@
+//! An async TLS echo server using tokio-rustls, preferring the X25519MLKEM768 key exchange.
+//!
+//! It requires OpenSSL 3.0 or later, and oqsprovider to be installed.
+//!
+//! The server generates a self-signed certificate for `localhost`, writes it to
+//! `$TMPDIR/rustls-liboqs-async-server.pem` so clients can trust it, then listens on the address given as the
+//! first argument (default `127.0.0.1:4443`). For each connection it prints the negotiated key exchange group
+//! and echoes back everything it receives. Try it with the async client example, or:
+//!
+//! ```sh
+//! openssl s_client -connect 127.0.0.1:4443 -groups X25519MLKEM768 -CAfile $TMPDIR/rustls-liboqs-async-server.pem
+//! ```
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
+use rustls::ServerConfig;
+use rustls_liboqs::{OqsProviderGuard, X25519MLKEM768};
+use std::error::Error;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::io::{copy, split, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_rustls::TlsAcceptor;
+
+const DEFAULT_ADDR: &str = "127.0.0.1:4443";
+
+/// Generates a self-signed certificate for `localhost`, and writes it to the temporary directory.
+fn self_signed_cert() -> Result<(CertificateDer<'static>, PrivateKeyDer<'static>), Box<dyn Error>> {
+    let certified = rcgen::generate_simple_self_signed(vec!["localhost".to_string()])?;
+
+    let path = std::env::temp_dir().join("rustls-liboqs-async-server.pem");
+    std::fs::write(&path, certified.cert.pem())?;
+    println!("Wrote certificate to {}", path.display());
+
+    let key = PrivatePkcs8KeyDer::from(certified.signing_key.serialize_der());
+    Ok((certified.cert.der().clone(), key.into()))
+}
+
+fn server_config() -> Result<ServerConfig, Box<dyn Error>> {
+    let (cert, key) = self_signed_cert()?;
+
+    // Prefer X25519MLKEM768, falling back to classical groups for clients without post-quantum support
+    let mut kx_groups = vec![X25519MLKEM768];
+    kx_groups.extend(rustls_openssl::ALL_KX_GROUPS);
+    let provider =
+        rustls_openssl::custom_provider(rustls_openssl::ALL_CIPHER_SUITES.to_vec(), kx_groups);
+
+    Ok(ServerConfig::builder_with_provider(Arc::new(provider))
+        .with_safe_default_protocol_versions()?
+        .with_no_client_auth()
+        .with_single_cert(vec![cert], key)?)
+}
+
+async fn handle(acceptor: TlsAcceptor, stream: TcpStream, peer: SocketAddr) -> std::io::Result<()> {
+    let stream = acceptor.accept(stream).await?;
+    let group = stream.get_ref().1.negotiated_key_exchange_group();
+    println!(
+        "{peer}: negotiated key exchange group {:?}",
+        group.map(|group| group.name())
+    );
+
+    let (mut reader, mut writer) = split(stream);
+    let echoed = copy(&mut reader, &mut writer).await?;
+    // Send close_notify, so the client knows the response is complete
+    writer.shutdown().await?;
+    println!("{peer}: echoed {echoed} bytes");
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let _providers = OqsProviderGuard::load()?;
+    env_logger::init();
+
+    let addr = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| DEFAULT_ADDR.to_string());
+    let acceptor = TlsAcceptor::from(Arc::new(server_config()?));
+    let listener = TcpListener::bind(&addr).await?;
+    println!("Listening on {addr}");
+
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let acceptor = acceptor.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle(acceptor, stream, peer).await {
+                eprintln!("{peer}: {e}");
+            }
+        });
+    }
+}
@@ -7,23 +7,19 @@
 //!
 //! Note that `unwrap()` is used to deal with networking errors; this is not something
 //! that is sensible outside of example code.
-use rustls_liboqs::X25519MLKEM768;
+use rustls_liboqs::{load_oqs_provider, X25519MLKEM768};
 use rustls_openssl::custom_provider;
 use std::io::{stdout, Read, Write};
 use std::net::TcpStream;
 use std::sync::Arc;
 
 fn main() {
-    use openssl::error::ErrorStack;
     use rustls_openssl::ALL_CIPHER_SUITES;
 
     let _default_provider =
         openssl::provider::Provider::load(None, "default").expect("Failed to load OQS provider.");
-    let _provider = openssl::provider::Provider::load(None, "oqsprovider")
-        .expect("Failed to load OQS provider.");
-    // OSSL_PROVIDER_load seems to leave errors on the stack, even
-    // when it succeeds.
-    let _ = ErrorStack::get();
+    // Set OQS_PROVIDER_PATH to load oqsprovider from a non-standard location.
+    let _provider = load_oqs_provider(None).expect("Failed to load OQS provider.");
 
     env_logger::init();
     let suites = ALL_CIPHER_SUITES.to_vec();
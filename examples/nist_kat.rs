@@ -0,0 +1,120 @@
+//! Verifies ML-KEM-768 decapsulation against NIST ACVP test vectors.
+//!
+//! It requires OpenSSL 3.0 or later, and oqsprovider to be installed.
+//!
+//! Usage: `cargo run --example nist_kat -- <path to ACVP JSON file>`. If no path is given,
+//! the sample vectors in `test-data/ML-KEM-768-encapDecap-FIPS203.json` are used.
+//!
+//! The sample vectors are the ML-KEM-768 test groups of the `internalProjection.json` file from
+//! the NIST ACVP server's [ML-KEM encapsulation/decapsulation test vectors]. To fetch the full set:
+//!
+//! ```sh
+//! curl -LO https://raw.githubusercontent.com/usnistgov/ACVP-Server/master/gen-val/json-files/ML-KEM-encapDecap-FIPS203/internalProjection.json
+//! ```
+//!
+//! Every test case in an ML-KEM-768 group that has a decapsulation key, ciphertext and shared
+//! secret is checked: the decapsulation key is loaded into a [`KeyExchange`], which is then
+//! completed with the ciphertext and compared against the expected shared secret.
+//!
+//! [ML-KEM encapsulation/decapsulation test vectors]: https://github.com/usnistgov/ACVP-Server/tree/master/gen-val/json-files/ML-KEM-encapDecap-FIPS203
+use foreign_types::ForeignType;
+use openssl::pkey::{PKey, Private};
+use rustls_liboqs::{KeyExchange, KxGroup};
+use serde_json::Value;
+use std::process::ExitCode;
+use std::ptr;
+
+fn hex(value: &Value) -> Option<Vec<u8>> {
+    let s = value.as_str()?;
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+fn private_key_from_raw(dk: &[u8]) -> Result<PKey<Private>, openssl::error::ErrorStack> {
+    unsafe {
+        let pkey = openssl_sys::EVP_PKEY_new_raw_private_key_ex(
+            ptr::null_mut(),
+            c"mlkem768".as_ptr(),
+            ptr::null(),
+            dk.as_ptr(),
+            dk.len(),
+        );
+        if pkey.is_null() {
+            Err(openssl::error::ErrorStack::get())
+        } else {
+            Ok(PKey::from_ptr(pkey))
+        }
+    }
+}
+
+fn decapsulate(ek: &[u8], dk: &[u8], c: &[u8]) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let der = private_key_from_raw(dk)?.private_key_to_der()?;
+    let kx = KeyExchange::from_raw_parts(ek.to_vec(), der, KxGroup::MLKEM768)?;
+    let secret = rustls::crypto::ActiveKeyExchange::complete(kx, c)?;
+    Ok(secret.secret_bytes().to_vec())
+}
+
+fn main() -> ExitCode {
+    let _default_provider = openssl::provider::Provider::load(None, "default")
+        .expect("Failed to load default provider.");
+    let _provider = openssl::provider::Provider::load(None, "oqsprovider")
+        .expect("Failed to load OQS provider.");
+    // OSSL_PROVIDER_load seems to leave errors on the stack, even
+    // when it succeeds.
+    let _ = openssl::error::ErrorStack::get();
+
+    let path = std::env::args().nth(1).unwrap_or_else(|| {
+        concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/test-data/ML-KEM-768-encapDecap-FIPS203.json"
+        )
+        .to_string()
+    });
+    let vectors: Value = serde_json::from_str(
+        &std::fs::read_to_string(&path).expect("Failed to read test vectors."),
+    )
+    .expect("Failed to parse test vectors.");
+
+    let mut passed = 0;
+    let mut failed = 0;
+    let groups = vectors["testGroups"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default();
+    for group in groups
+        .iter()
+        .filter(|group| group["parameterSet"] == "ML-KEM-768")
+    {
+        for test in group["tests"].as_array().into_iter().flatten() {
+            // Decapsulation groups have a single key pair, encapsulation groups have one per test.
+            let ek = hex(&test["ek"]).or_else(|| hex(&group["ek"]));
+            let dk = hex(&test["dk"]).or_else(|| hex(&group["dk"]));
+            let (Some(ek), Some(dk), Some(c), Some(k)) = (ek, dk, hex(&test["c"]), hex(&test["k"]))
+            else {
+                continue;
+            };
+
+            let tc_id = &test["tcId"];
+            match decapsulate(&ek, &dk, &c) {
+                Ok(secret) if secret == k => passed += 1,
+                Ok(_) => {
+                    eprintln!("tcId {tc_id}: shared secret mismatch");
+                    failed += 1;
+                }
+                Err(e) => {
+                    eprintln!("tcId {tc_id}: {e}");
+                    failed += 1;
+                }
+            }
+        }
+    }
+
+    println!("{passed} passed, {failed} failed");
+    if failed == 0 && passed > 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
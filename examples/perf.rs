@@ -0,0 +1,59 @@
+//! Quick performance check of the key exchange groups provided by this crate.
+//!
+//! It requires OpenSSL 3.0 or later, and oqsprovider to be installed.
+//!
+//! For each group it times key generation, encapsulation and decapsulation, and prints
+//! the mean latency of each operation as a markdown table. The number of iterations
+//! defaults to 1000 and can be passed as the first argument.
+use rustls_liboqs::ALL_KX_GROUPS;
+use std::time::{Duration, Instant};
+
+fn main() {
+    use openssl::error::ErrorStack;
+
+    let _default_provider = openssl::provider::Provider::load(None, "default")
+        .expect("Failed to load default provider.");
+    let _provider = openssl::provider::Provider::load(None, "oqsprovider")
+        .expect("Failed to load OQS provider.");
+    // OSSL_PROVIDER_load seems to leave errors on the stack, even
+    // when it succeeds.
+    let _ = ErrorStack::get();
+
+    let iterations: u32 = std::env::args()
+        .nth(1)
+        .map(|arg| arg.parse().expect("iterations must be a number"))
+        .unwrap_or(1000);
+
+    println!("| Group | KeygenMs | EncapMs | DecapMs | TotalMs |");
+    println!("|-------|----------|---------|---------|---------|");
+
+    for group in ALL_KX_GROUPS {
+        let mut keygen = Duration::ZERO;
+        let mut encap = Duration::ZERO;
+        let mut decap = Duration::ZERO;
+
+        for _ in 0..iterations {
+            let start = Instant::now();
+            let kx = group.start().unwrap();
+            keygen += start.elapsed();
+
+            let start = Instant::now();
+            let completed = group.start_and_complete(kx.pub_key()).unwrap();
+            encap += start.elapsed();
+
+            let start = Instant::now();
+            kx.complete(&completed.pub_key).unwrap();
+            decap += start.elapsed();
+        }
+
+        let mean_ms = |total: Duration| total.as_secs_f64() * 1000.0 / f64::from(iterations);
+        println!(
+            "| {:?} | {:.3} | {:.3} | {:.3} | {:.3} |",
+            group.name(),
+            mean_ms(keygen),
+            mean_ms(encap),
+            mean_ms(decap),
+            mean_ms(keygen + encap + decap)
+        );
+    }
+}
@@ -0,0 +1,100 @@
+//! A QUIC client and server in the same process using quinn, with the X25519MLKEM768 key exchange.
+//!
+//! It requires OpenSSL 3.0 or later, and oqsprovider to be installed.
+//!
+//! The server uses a self-signed certificate and echoes a message on a bidirectional stream. quinn doesn't expose
+//! the negotiated key exchange group, so the server only offers X25519MLKEM768: a completed handshake means it was
+//! used. The client also offers classical groups, as it would to connect to arbitrary servers.
+use quinn::crypto::rustls::{HandshakeData, QuicClientConfig, QuicServerConfig};
+use quinn::Endpoint;
+use rustls::crypto::{CryptoProvider, SupportedKxGroup};
+use rustls::pki_types::PrivatePkcs8KeyDer;
+use rustls::{ClientConfig, RootCertStore, ServerConfig};
+use rustls_liboqs::{OqsProviderGuard, X25519MLKEM768};
+use std::error::Error;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::timeout;
+
+const ALPN: &[u8] = b"echo";
+const MESSAGE: &[u8] = b"Hello, post-quantum QUIC!";
+/// How long to wait for the handshake and the echoed message.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+fn provider(kx_groups: Vec<&'static dyn SupportedKxGroup>) -> Arc<CryptoProvider> {
+    Arc::new(rustls_openssl::custom_provider(
+        rustls_openssl::ALL_CIPHER_SUITES.to_vec(),
+        kx_groups,
+    ))
+}
+
+/// Accepts a single connection and echoes the first bidirectional stream.
+async fn serve(endpoint: Endpoint) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let incoming = endpoint.accept().await.ok_or("server endpoint closed")?;
+    let connection = incoming.await?;
+    let (mut send, mut recv) = connection.accept_bi().await?;
+    let message = recv.read_to_end(MESSAGE.len()).await?;
+    send.write_all(&message).await?;
+    send.finish()?;
+    connection.closed().await;
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
+    let _providers = OqsProviderGuard::load()?;
+    env_logger::init();
+
+    let certified = rcgen::generate_simple_self_signed(vec!["localhost".to_string()])?;
+    let cert = certified.cert.der().clone();
+    let key = PrivatePkcs8KeyDer::from(certified.signing_key.serialize_der());
+
+    let mut server_crypto = ServerConfig::builder_with_provider(provider(vec![X25519MLKEM768]))
+        .with_protocol_versions(&[&rustls::version::TLS13])?
+        .with_no_client_auth()
+        .with_single_cert(vec![cert.clone()], key.into())?;
+    server_crypto.alpn_protocols = vec![ALPN.to_vec()];
+    let server_config =
+        quinn::ServerConfig::with_crypto(Arc::new(QuicServerConfig::try_from(server_crypto)?));
+    let server = Endpoint::server(server_config, "127.0.0.1:0".parse()?)?;
+    let server_addr = server.local_addr()?;
+    let server_task = tokio::spawn(serve(server));
+
+    let mut roots = RootCertStore::empty();
+    roots.add(cert)?;
+    let mut kx_groups = vec![X25519MLKEM768];
+    kx_groups.extend(rustls_openssl::ALL_KX_GROUPS);
+    let mut client_crypto = ClientConfig::builder_with_provider(provider(kx_groups))
+        .with_protocol_versions(&[&rustls::version::TLS13])?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    client_crypto.alpn_protocols = vec![ALPN.to_vec()];
+
+    let mut client = Endpoint::client("127.0.0.1:0".parse()?)?;
+    client.set_default_client_config(quinn::ClientConfig::new(Arc::new(
+        QuicClientConfig::try_from(client_crypto)?,
+    )));
+    let connection = timeout(TIMEOUT, client.connect(server_addr, "localhost")?).await??;
+
+    let handshake = connection
+        .handshake_data()
+        .and_then(|data| data.downcast::<HandshakeData>().ok())
+        .ok_or("missing handshake data")?;
+    assert_eq!(handshake.protocol.as_deref(), Some(ALPN));
+    println!(
+        "Connected to {server_addr} using {:?}",
+        X25519MLKEM768.name()
+    );
+
+    let (mut send, mut recv) = connection.open_bi().await?;
+    send.write_all(MESSAGE).await?;
+    send.finish()?;
+    let echoed = timeout(TIMEOUT, recv.read_to_end(MESSAGE.len())).await??;
+    assert_eq!(echoed, MESSAGE);
+    println!("Received: {}", String::from_utf8_lossy(&echoed));
+
+    connection.close(0u32.into(), b"done");
+    client.wait_idle().await;
+    server_task.await??;
+    Ok(())
+}
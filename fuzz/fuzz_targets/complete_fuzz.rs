@@ -0,0 +1,17 @@
+//! Fuzzes decapsulation of arbitrary ciphertexts.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rustls_liboqs::{OqsProviderGuard, MLKEM768};
+use std::sync::OnceLock;
+
+fuzz_target!(|ciphertext: &[u8]| {
+    static PROVIDERS: OnceLock<OqsProviderGuard> = OnceLock::new();
+    PROVIDERS.get_or_init(|| OqsProviderGuard::load().unwrap());
+
+    let kx = MLKEM768.start().unwrap();
+    match kx.complete(ciphertext) {
+        Ok(secret) => assert!(!secret.secret_bytes().is_empty()),
+        Err(err) => assert!(matches!(err, rustls::Error::Other(_)), "{err:?}"),
+    }
+});
@@ -0,0 +1,33 @@
+//! Fuzzes completing the classical component of hybrid key exchanges with arbitrary peer public keys.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rustls::crypto::SupportedKxGroup;
+use rustls::{Error, OtherError};
+use rustls_liboqs::{
+    KemError, OqsProviderGuard, P256MLKEM768, P384MLKEM1024, X25519MLKEM768, X448MLKEM1024,
+};
+use std::sync::OnceLock;
+
+const GROUPS: [&dyn SupportedKxGroup; 4] =
+    [X25519MLKEM768, P256MLKEM768, P384MLKEM1024, X448MLKEM1024];
+
+fuzz_target!(|input: (u8, &[u8])| {
+    static PROVIDERS: OnceLock<OqsProviderGuard> = OnceLock::new();
+    PROVIDERS.get_or_init(|| OqsProviderGuard::load().unwrap());
+
+    let (group, peer_pub_key) = input;
+    let kx = GROUPS[usize::from(group) % GROUPS.len()].start().unwrap();
+    assert!(kx.hybrid_component().is_some());
+    match kx.complete_hybrid_component(peer_pub_key) {
+        Ok(secret) => assert!(!secret.secret_bytes().is_empty()),
+        Err(Error::Other(OtherError(err))) => assert!(
+            matches!(
+                err.downcast_ref::<KemError>(),
+                Some(KemError::ClassicalKeyAgreement { .. })
+            ),
+            "{err:?}"
+        ),
+        Err(err) => panic!("unexpected error: {err:?}"),
+    }
+});
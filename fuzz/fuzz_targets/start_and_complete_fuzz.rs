@@ -0,0 +1,22 @@
+//! Fuzzes encapsulation to arbitrary peer public keys.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rustls_liboqs::{OqsProviderGuard, MLKEM768, X25519MLKEM768};
+use std::sync::OnceLock;
+
+fuzz_target!(|input: (bool, &[u8])| {
+    static PROVIDERS: OnceLock<OqsProviderGuard> = OnceLock::new();
+    PROVIDERS.get_or_init(|| OqsProviderGuard::load().unwrap());
+
+    let (hybrid, peer_pub_key) = input;
+    let group = if hybrid { X25519MLKEM768 } else { MLKEM768 };
+    match group.start_and_complete(peer_pub_key) {
+        Ok(completed) => {
+            assert_eq!(completed.group, group.name());
+            assert!(!completed.pub_key.is_empty());
+            assert!(!completed.secret.secret_bytes().is_empty());
+        }
+        Err(err) => assert!(matches!(err, rustls::Error::Other(_)), "{err:?}"),
+    }
+});
@@ -0,0 +1,114 @@
+//! Helpers for configuring rustls with the post-quantum key exchange groups.
+use crate::all_kx_groups;
+use rustls::crypto::SupportedKxGroup;
+
+/// Returns the post-quantum key exchange groups in the recommended order, for use as the start of a
+/// [`CryptoProvider`](rustls::crypto::CryptoProvider)'s `kx_groups`.
+///
+/// Hybrid groups come first, then pure post-quantum groups; see [`ALL_KX_GROUPS`](crate::ALL_KX_GROUPS). Classical
+/// groups should be appended so that peers without post-quantum support can still connect.
+pub fn make_pq_kx_groups() -> Vec<&'static dyn SupportedKxGroup> {
+    all_kx_groups().to_vec()
+}
+
+/// Installs a process-wide default [`CryptoProvider`](rustls::crypto::CryptoProvider) that uses
+/// [rustls-openssl](https://docs.rs/rustls-openssl) for cryptography, with the groups from [`make_pq_kx_groups`]
+/// preferred over rustls-openssl's classical groups.
+///
+/// The default and oqsprovider OpenSSL providers are loaded, and stay loaded for the rest of the process.
+/// An error is returned if they can't be loaded, or if a default provider is already installed.
+#[cfg(feature = "rustls-openssl")]
+pub fn install_default_provider() -> Result<(), rustls::Error> {
+    load_providers()?;
+
+    let mut kx_groups = make_pq_kx_groups();
+    kx_groups.extend(rustls_openssl::ALL_KX_GROUPS);
+    rustls_openssl::custom_provider(rustls_openssl::ALL_CIPHER_SUITES.to_vec(), kx_groups)
+        .install_default()
+        .map_err(|_| rustls::Error::General("a default CryptoProvider is already installed".into()))
+}
+
+/// Builds a [`ClientConfig`](rustls::ClientConfig) that trusts `roots` and offers `groups`, using
+/// [rustls-openssl](https://docs.rs/rustls-openssl) for cryptography.
+///
+/// The default and oqsprovider OpenSSL providers are loaded as for [`install_default_provider`], but no default
+/// provider is installed. `groups` is used as given, so include classical groups, e.g from [`make_pq_kx_groups`]
+/// followed by `rustls_openssl::ALL_KX_GROUPS`, to connect to servers without post-quantum support.
+#[cfg(feature = "rustls-openssl")]
+pub fn make_client_config(
+    roots: rustls::RootCertStore,
+    groups: Vec<&'static dyn SupportedKxGroup>,
+) -> Result<rustls::ClientConfig, rustls::Error> {
+    load_providers()?;
+
+    let provider =
+        rustls_openssl::custom_provider(rustls_openssl::ALL_CIPHER_SUITES.to_vec(), groups);
+    Ok(
+        rustls::ClientConfig::builder_with_provider(std::sync::Arc::new(provider))
+            .with_safe_default_protocol_versions()?
+            .with_root_certificates(roots)
+            .with_no_client_auth(),
+    )
+}
+
+/// Builds a [`ClientConfig`](rustls::ClientConfig) with [`make_client_config`] that trusts the Mozilla root
+/// certificates from [webpki-roots](https://docs.rs/webpki-roots).
+#[cfg(all(feature = "rustls-openssl", feature = "webpki-roots"))]
+pub fn make_client_config_with_webpki_roots(
+    groups: Vec<&'static dyn SupportedKxGroup>,
+) -> Result<rustls::ClientConfig, rustls::Error> {
+    let roots = rustls::RootCertStore {
+        roots: webpki_roots::TLS_SERVER_ROOTS.into(),
+    };
+    make_client_config(roots, groups)
+}
+
+/// Loads the OpenSSL providers for the lifetime of the process.
+#[cfg(feature = "rustls-openssl")]
+fn load_providers() -> Result<(), rustls::Error> {
+    use crate::OqsProviderGuard;
+    use std::sync::{Arc, OnceLock};
+
+    static PROVIDERS: OnceLock<OqsProviderGuard> = OnceLock::new();
+    if PROVIDERS.get().is_none() {
+        let guard = OqsProviderGuard::load()
+            .map_err(|e| rustls::Error::Other(rustls::OtherError(Arc::new(e))))?;
+        // Another thread may have won the race, in which case our guard is dropped without unloading anything
+        let _ = PROVIDERS.set(guard);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::make_pq_kx_groups;
+    use crate::{hybrid_kx_groups, X25519MLKEM768};
+
+    #[test]
+    fn pq_kx_groups_prefer_hybrids() {
+        let groups = make_pq_kx_groups();
+        let hybrid = hybrid_kx_groups();
+        assert_eq!(groups.len(), crate::ALL_KX_GROUPS.len());
+        for (group, hybrid) in groups.iter().zip(hybrid) {
+            assert_eq!(group.name(), hybrid.name());
+        }
+        assert!(groups
+            .iter()
+            .any(|group| group.name() == X25519MLKEM768.name()));
+    }
+
+    #[cfg(feature = "rustls-openssl")]
+    #[test]
+    fn install_default_provider() {
+        super::install_default_provider().unwrap();
+
+        let provider = rustls::crypto::CryptoProvider::get_default().unwrap();
+        assert_eq!(provider.kx_groups[0].name(), crate::ALL_KX_GROUPS[0].name());
+        assert!(provider
+            .kx_groups
+            .iter()
+            .any(|group| group.name() == rustls::NamedGroup::X25519));
+
+        assert!(super::install_default_provider().is_err());
+    }
+}
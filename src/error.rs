@@ -0,0 +1,160 @@
+//! Errors returned by this crate.
+use openssl::error::ErrorStack;
+use rustls::OtherError;
+use std::fmt;
+use std::sync::Arc;
+
+/// An error from a key encapsulation mechanism (KEM) operation.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum KemError {
+    /// OpenSSL's random number generator has not been seeded with enough entropy.
+    InsufficientEntropy,
+    /// An OpenSSL provider required for post-quantum groups is not loaded.
+    ProviderNotLoaded {
+        /// The provider name.
+        name: String,
+    },
+    /// A public key is malformed.
+    InvalidPublicKey {
+        /// The OpenSSL algorithm name.
+        algorithm: String,
+        /// The expected length of the public key, if known.
+        expected_len: Option<usize>,
+        /// The length of the public key.
+        got_len: usize,
+    },
+    /// A ciphertext is malformed.
+    InvalidCiphertext {
+        /// The OpenSSL algorithm name.
+        algorithm: String,
+        /// The expected length of the ciphertext.
+        expected_len: usize,
+        /// The length of the ciphertext.
+        got_len: usize,
+    },
+    /// Key generation failed.
+    Keygen {
+        /// The OpenSSL algorithm name.
+        algorithm: String,
+        /// The underlying OpenSSL error.
+        source: ErrorStack,
+    },
+    /// Encapsulation to a peer's public key failed.
+    Encapsulate {
+        /// The OpenSSL algorithm name.
+        algorithm: String,
+        /// The underlying OpenSSL error.
+        source: ErrorStack,
+    },
+    /// Decapsulation of a peer's ciphertext failed.
+    Decapsulate {
+        /// The OpenSSL algorithm name.
+        algorithm: String,
+        /// The underlying OpenSSL error.
+        source: ErrorStack,
+    },
+    /// A private key could not be encoded or decoded.
+    InvalidPrivateKey {
+        /// The OpenSSL algorithm name.
+        algorithm: String,
+        /// The underlying OpenSSL error.
+        source: ErrorStack,
+    },
+    /// The key agreement of the classical component of a hybrid group failed, e.g because the peer's public key
+    /// is malformed.
+    ClassicalKeyAgreement {
+        /// The OpenSSL algorithm name of the hybrid group.
+        algorithm: String,
+        /// The underlying OpenSSL error.
+        source: ErrorStack,
+    },
+    /// Computing the HMAC of a shared secret failed.
+    SecretMac {
+        /// The underlying OpenSSL error.
+        source: ErrorStack,
+    },
+    /// The HMAC of a shared secret didn't match the MAC sent by the peer.
+    SecretMacMismatch,
+}
+
+impl fmt::Display for KemError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KemError::InsufficientEntropy => {
+                write!(
+                    f,
+                    "OpenSSL random number generator has insufficient entropy"
+                )
+            }
+            KemError::ProviderNotLoaded { name } => write!(
+                f,
+                "{name} is not loaded; call openssl::provider::Provider::load(None, \"{name}\") before using post-quantum groups, \
+                 and set OPENSSL_MODULES if it isn't installed in OpenSSL's default module directory"
+            ),
+            KemError::Keygen { algorithm, source } => {
+                write!(f, "{algorithm} key generation failed: {source}")
+            }
+            KemError::Encapsulate { algorithm, source } => {
+                write!(f, "{algorithm} encapsulation failed: {source}")
+            }
+            KemError::Decapsulate { algorithm, source } => {
+                write!(f, "{algorithm} decapsulation failed: {source}")
+            }
+            KemError::InvalidPublicKey {
+                algorithm,
+                expected_len: Some(expected_len),
+                got_len,
+            } => write!(
+                f,
+                "invalid {algorithm} public key: expected {expected_len} bytes, got {got_len}"
+            ),
+            KemError::InvalidPublicKey {
+                algorithm,
+                expected_len: None,
+                got_len,
+            } => write!(f, "invalid {algorithm} public key of {got_len} bytes"),
+            KemError::InvalidCiphertext {
+                algorithm,
+                expected_len,
+                got_len,
+            } => write!(
+                f,
+                "invalid {algorithm} ciphertext: expected {expected_len} bytes, got {got_len}"
+            ),
+            KemError::InvalidPrivateKey { algorithm, source } => {
+                write!(f, "invalid {algorithm} private key: {source}")
+            }
+            KemError::ClassicalKeyAgreement { algorithm, source } => {
+                write!(f, "{algorithm} classical key agreement failed: {source}")
+            }
+            KemError::SecretMac { source } => write!(f, "shared secret MAC failed: {source}"),
+            KemError::SecretMacMismatch => write!(f, "shared secret MAC verification failed"),
+        }
+    }
+}
+
+impl std::error::Error for KemError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            KemError::Keygen { source, .. }
+            | KemError::Encapsulate { source, .. }
+            | KemError::Decapsulate { source, .. }
+            | KemError::InvalidPrivateKey { source, .. }
+            | KemError::ClassicalKeyAgreement { source, .. }
+            | KemError::SecretMac { source } => Some(source),
+            KemError::InsufficientEntropy
+            | KemError::SecretMacMismatch
+            | KemError::ProviderNotLoaded { .. }
+            | KemError::InvalidPublicKey { .. }
+            | KemError::InvalidCiphertext { .. } => None,
+        }
+    }
+}
+
+/// Converts to [`rustls::Error::Other`], so the [`KemError`] can be recovered by downcasting.
+impl From<KemError> for rustls::Error {
+    fn from(error: KemError) -> Self {
+        rustls::Error::Other(OtherError(Arc::new(error)))
+    }
+}
@@ -0,0 +1,106 @@
+//! Falling back to another key exchange group when one is unavailable.
+use rustls::crypto::{ActiveKeyExchange, CompletedKeyExchange, SupportedKxGroup};
+use rustls::{Error, NamedGroup, ProtocolVersion};
+
+/// A key exchange group that starts a key exchange with `fallback` if `primary` fails, e.g because oqsprovider
+/// isn't loaded or doesn't support the primary's algorithm.
+///
+/// The group is named after the primary, so is offered to peers as the primary. If [`SupportedKxGroup::start`]
+/// falls back, the returned key exchange's [`ActiveKeyExchange::group`] is the fallback's group, and the key share
+/// is sent for that group instead. Peers only accept key shares for groups in the client's supported groups, so
+/// the fallback should also be in the [`CryptoProvider`](rustls::crypto::CryptoProvider)'s `kx_groups`.
+///
+/// [`SupportedKxGroup::start_and_complete`] doesn't fall back, as the peer's public key is for the primary's group.
+///
+/// ```
+/// use rustls::crypto::SupportedKxGroup;
+/// use rustls_liboqs::{FallbackKxGroup, X25519MLKEM768};
+///
+/// static GROUP: FallbackKxGroup = FallbackKxGroup {
+///     primary: X25519MLKEM768,
+///     fallback: rustls::crypto::aws_lc_rs::kx_group::X25519,
+/// };
+/// assert_eq!(GROUP.name(), rustls::NamedGroup::X25519MLKEM768);
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct FallbackKxGroup {
+    /// The group to try first, which names this group.
+    pub primary: &'static dyn SupportedKxGroup,
+    /// The group to use if the primary fails to start a key exchange.
+    pub fallback: &'static dyn SupportedKxGroup,
+}
+
+impl SupportedKxGroup for FallbackKxGroup {
+    fn start(&self) -> Result<Box<dyn ActiveKeyExchange>, Error> {
+        self.primary.start().or_else(|_e| {
+            #[cfg(feature = "log")]
+            log::warn!(
+                "{:?} key exchange failed, falling back to {:?}: {_e}",
+                self.primary.name(),
+                self.fallback.name()
+            );
+            self.fallback.start()
+        })
+    }
+
+    fn name(&self) -> NamedGroup {
+        self.primary.name()
+    }
+
+    fn usable_for_version(&self, version: ProtocolVersion) -> bool {
+        self.primary.usable_for_version(version)
+    }
+
+    fn ffdhe_group(&self) -> Option<rustls::ffdhe_groups::FfdheGroup<'static>> {
+        self.primary.ffdhe_group()
+    }
+
+    fn start_and_complete(&self, peer_pub_key: &[u8]) -> Result<CompletedKeyExchange, Error> {
+        self.primary.start_and_complete(peer_pub_key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FallbackKxGroup;
+    use crate::test_utils::{DisabledKxGroup, MockKxGroup};
+    use rustls::crypto::SupportedKxGroup;
+    use rustls::NamedGroup;
+
+    static DISABLED: DisabledKxGroup = DisabledKxGroup(NamedGroup::X25519MLKEM768);
+
+    fn mock(named_group: NamedGroup) -> &'static MockKxGroup {
+        Box::leak(Box::new(MockKxGroup {
+            named_group,
+            shared_secret: vec![1; 32],
+        }))
+    }
+
+    #[test]
+    fn falls_back() {
+        let group = FallbackKxGroup {
+            primary: &DISABLED,
+            fallback: mock(NamedGroup::X25519),
+        };
+        assert_eq!(group.name(), NamedGroup::X25519MLKEM768);
+
+        for _ in 0..3 {
+            let kx = group.start().unwrap();
+            assert_eq!(kx.group(), NamedGroup::X25519);
+            assert_eq!(kx.pub_key(), MockKxGroup::PUB_KEY);
+        }
+
+        // The peer's public key is for the primary, so this doesn't fall back
+        assert!(group.start_and_complete(MockKxGroup::PUB_KEY).is_err());
+    }
+
+    #[test]
+    fn uses_primary() {
+        let group = FallbackKxGroup {
+            primary: mock(NamedGroup::MLKEM768),
+            fallback: mock(NamedGroup::X25519),
+        };
+        assert_eq!(group.name(), NamedGroup::MLKEM768);
+        assert_eq!(group.start().unwrap().group(), NamedGroup::MLKEM768);
+    }
+}
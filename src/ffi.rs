@@ -1,5 +1,39 @@
 //! OpenSSL wrappers not available in the `openssl` crate.
-use std::ffi::{c_char, c_uchar};
+//!
+//! These extension traits wrap the OpenSSL 3 KEM and provider parameter APIs used by this crate, so downstream
+//! crates can build their own key exchange groups without duplicating the unsafe FFI code.
+//!
+//! The traits are thin wrappers over the OpenSSL functions of the same name, and will change if the `openssl`
+//! crate gains equivalent methods. Like the rest of this crate they are experimental, so may change between
+//! minor versions.
+//!
+//! # Example
+//!
+//! Encapsulating a shared secret to a peer's ML-KEM-768 public key, and decapsulating it again:
+//!
+//! ```no_run
+//! use openssl::pkey::{PKey, Public};
+//! use openssl::pkey_ctx::PkeyCtx;
+//! use rustls_liboqs::ffi::{PKeyRefExt, PkeyCtxExt, PkeyCtxRefKemExt, PkeyExt};
+//!
+//! # fn main() -> Result<(), openssl::error::ErrorStack> {
+//! let mut ctx = PkeyCtx::<()>::new_from_name(b"mlkem768\0")?;
+//! ctx.keygen_init()?;
+//! let private_key = ctx.keygen()?;
+//! let encoded_public_key = private_key.get_octet_string_param(b"encoded-pub-key\0")?;
+//!
+//! let public_key = PKey::<Public>::from_encoded_public_key(&encoded_public_key, b"mlkem768\0")?;
+//! let mut ctx = PkeyCtx::new(&public_key)?;
+//! ctx.encapsulate_init()?;
+//! let (ciphertext, secret) = ctx.encapsulate_to_vec()?;
+//!
+//! let ctx = PkeyCtx::new(&private_key)?;
+//! ctx.decapsulate_init()?;
+//! assert_eq!(ctx.decapsulate_to_vec(&ciphertext)?, secret);
+//! # Ok(())
+//! # }
+//! ```
+use std::ffi::{c_char, c_uchar, c_void, CStr, CString};
 use std::ptr;
 
 use foreign_types::{ForeignType, ForeignTypeRef};
@@ -7,8 +41,12 @@ use openssl::{
     error::ErrorStack,
     pkey::{PKey, PKeyRef, Public},
     pkey_ctx::{PkeyCtx, PkeyCtxRef},
+    provider::ProviderRef,
 };
-use openssl_sys::{c_int, EVP_PKEY_new, EVP_PKEY, EVP_PKEY_CTX, OSSL_LIB_CTX, OSSL_PARAM};
+use openssl_sys::{
+    c_int, EVP_PKEY_new, EVP_PKEY, EVP_PKEY_CTX, OSSL_LIB_CTX, OSSL_PARAM, OSSL_PROVIDER,
+};
+use zeroize::Zeroizing;
 
 #[inline]
 fn cvt_p<T>(r: *mut T) -> Result<*mut T, ErrorStack> {
@@ -28,35 +66,299 @@ fn cvt(r: c_int) -> Result<c_int, ErrorStack> {
     }
 }
 
+/// Returns whether OpenSSL's random number generator has been seeded with enough entropy.
+pub(crate) fn rand_status() -> bool {
+    openssl_sys::init();
+    unsafe { openssl_sys::RAND_status() == 1 }
+}
+
+/// Returns whether the named provider is loaded and available in the default library context.
+pub(crate) fn provider_available(name: &CStr) -> bool {
+    openssl_sys::init();
+    unsafe { OSSL_PROVIDER_available(ptr::null_mut(), name.as_ptr()) == 1 }
+}
+
+/// Returns the names of all KEM algorithms provided by the loaded OpenSSL providers.
+///
+/// This can be used to check the installed oqsprovider supports the expected algorithms.
+pub fn list_supported_kem_algorithms() -> Result<Vec<String>, ErrorStack> {
+    extern "C" fn collect_name(kem: *mut EVP_KEM, arg: *mut c_void) {
+        let names = unsafe { &mut *arg.cast::<Vec<String>>() };
+        let name = unsafe { EVP_KEM_get0_name(kem) };
+        if !name.is_null() {
+            names.push(
+                unsafe { CStr::from_ptr(name) }
+                    .to_string_lossy()
+                    .into_owned(),
+            );
+        }
+    }
+
+    openssl_sys::init();
+    let mut names = Vec::new();
+    unsafe {
+        EVP_KEM_do_all_provided(
+            ptr::null_mut(),
+            collect_name,
+            (&mut names as *mut Vec<String>).cast(),
+        );
+    }
+    // Fetching the algorithms may leave errors for providers that failed to initialize
+    let _ = ErrorStack::get();
+    Ok(names)
+}
+
+/// The sizes used by a KEM algorithm.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KemAlgorithmInfo {
+    /// The algorithm name.
+    pub name: String,
+    /// The size of encoded public keys in bytes.
+    pub pub_key_size: usize,
+    /// The size of ciphertexts in bytes.
+    pub ciphertext_size: usize,
+    /// The size of shared secrets in bytes.
+    pub secret_size: usize,
+}
+
+/// Returns the names and sizes of all KEM algorithms provided by the loaded OpenSSL providers.
+///
+/// The sizes are found by generating a key for each algorithm, so this is relatively slow. Algorithms that
+/// can't be used without further configuration, such as OpenSSL's RSA KEM, are skipped.
+pub fn supported_kem_algorithms() -> Result<Vec<KemAlgorithmInfo>, ErrorStack> {
+    let infos = list_supported_kem_algorithms()?
+        .into_iter()
+        .filter_map(|name| kem_algorithm_info(name).ok())
+        .collect();
+    let _ = ErrorStack::get();
+    Ok(infos)
+}
+
+fn kem_algorithm_info(name: String) -> Result<KemAlgorithmInfo, ErrorStack> {
+    const OSSL_PKEY_PARAM_ENCODED_PUB_KEY: &[u8] = b"encoded-pub-key\0";
+
+    let c_name = CString::new(name.as_str()).map_err(|_| ErrorStack::get())?;
+    let mut ctx = unsafe {
+        PkeyCtx::<()>::from_ptr(cvt_p(EVP_PKEY_CTX_new_from_name(
+            ptr::null_mut(),
+            c_name.as_ptr(),
+            ptr::null(),
+        ))?)
+    };
+    ctx.keygen_init()?;
+    let key = ctx.keygen()?;
+    let pub_key_size = key
+        .get_octet_string_param(OSSL_PKEY_PARAM_ENCODED_PUB_KEY)?
+        .len();
+
+    let ctx = PkeyCtx::new(&key)?;
+    ctx.encapsulate_init()?;
+    let mut ciphertext_size = 0;
+    let mut secret_size = 0;
+    unsafe {
+        cvt(EVP_PKEY_encapsulate(
+            ctx.as_ptr(),
+            ptr::null_mut(),
+            &mut ciphertext_size,
+            ptr::null_mut(),
+            &mut secret_size,
+        ))?;
+    }
+
+    Ok(KemAlgorithmInfo {
+        name,
+        pub_key_size,
+        ciphertext_size,
+        secret_size,
+    })
+}
+
+/// Returns whether an algorithm is provided by the loaded OpenSSL providers.
+///
+/// The algorithm name should be a null terminated string, e.g `b"mlkem768\0"`. This is useful for feature
+/// detection, as different oqsprovider versions support different algorithms.
+pub fn is_algorithm_available(algorithm_name: &[u8]) -> bool {
+    let Ok(name) = CStr::from_bytes_with_nul(algorithm_name) else {
+        return false;
+    };
+
+    openssl_sys::init();
+    let ctx = unsafe { EVP_PKEY_CTX_new_from_name(ptr::null_mut(), name.as_ptr(), ptr::null()) };
+    if ctx.is_null() {
+        // Don't leave the failure on the thread's error stack
+        let _ = ErrorStack::get();
+        false
+    } else {
+        unsafe { openssl_sys::EVP_PKEY_CTX_free(ctx) };
+        true
+    }
+}
+
+/// Returns the version of the loaded oqsprovider, e.g `"0.7.0"`.
+///
+/// Returns `None` if oqsprovider isn't loaded, or doesn't report a version.
+pub fn oqs_provider_version() -> Option<String> {
+    provider_version(c"oqsprovider")
+}
+
+/// Returns the version reported by a loaded provider.
+fn provider_version(name: &CStr) -> Option<String> {
+    struct Search<'a> {
+        name: &'a CStr,
+        version: Option<String>,
+    }
+
+    extern "C" fn get_version(provider: *mut OSSL_PROVIDER, arg: *mut c_void) -> c_int {
+        let search = unsafe { &mut *arg.cast::<Search>() };
+        let name = unsafe { OSSL_PROVIDER_get0_name(provider) };
+        if name.is_null() || unsafe { CStr::from_ptr(name) } != search.name {
+            // Continue iterating
+            return 1;
+        }
+        search.version = unsafe { get_version_param(provider) };
+        0
+    }
+
+    openssl_sys::init();
+    let mut search = Search {
+        name,
+        version: None,
+    };
+    unsafe {
+        OSSL_PROVIDER_do_all(
+            ptr::null_mut(),
+            get_version,
+            (&mut search as *mut Search).cast(),
+        );
+    }
+    let _ = ErrorStack::get();
+    search.version
+}
+
+/// Returns the version reported by a provider, which may have been loaded from a path rather than by name.
+pub(crate) fn loaded_provider_version(provider: &ProviderRef) -> Option<String> {
+    let version = unsafe { get_version_param(provider.as_ptr()) };
+    let _ = ErrorStack::get();
+    version
+}
+
+/// Reads the provider's `version` parameter.
+///
+/// # Safety
+///
+/// `provider` must be a valid, loaded provider.
+unsafe fn get_version_param(provider: *mut OSSL_PROVIDER) -> Option<String> {
+    const OSSL_PROV_PARAM_VERSION: &[u8] = b"version\0";
+    let mut version: *mut c_char = ptr::null_mut();
+    let mut params = [
+        OSSL_PARAM_construct_utf8_ptr(OSSL_PROV_PARAM_VERSION.as_ptr().cast(), &mut version, 0),
+        OSSL_PARAM_construct_end(),
+    ];
+    if OSSL_PROVIDER_get_params(provider, params.as_mut_ptr()) == 1 && !version.is_null() {
+        Some(CStr::from_ptr(version).to_string_lossy().into_owned())
+    } else {
+        None
+    }
+}
+
+/// Pushes an EVP error with the given reason onto the OpenSSL error stack, and returns the stack.
+fn evp_error(reason: c_int, func: &CStr) -> ErrorStack {
+    const ERR_LIB_EVP: c_int = 6;
+
+    unsafe {
+        openssl_sys::ERR_new();
+        openssl_sys::ERR_set_debug(
+            concat!(file!(), "\0").as_ptr().cast(),
+            line!() as c_int,
+            func.as_ptr(),
+        );
+        openssl_sys::ERR_set_error(ERR_LIB_EVP, reason, ptr::null());
+    }
+    ErrorStack::get()
+}
+
+/// Pushes an error for an output buffer that is too small onto the OpenSSL error stack, and returns the stack.
+fn buffer_too_small() -> ErrorStack {
+    const EVP_R_BUFFER_TOO_SMALL: c_int = 155;
+    evp_error(EVP_R_BUFFER_TOO_SMALL, c"buffer_too_small")
+}
+
+/// Checks that `name` is a null terminated string with no interior nulls, so it can be passed to OpenSSL.
+///
+/// Otherwise pushes an invalid argument error onto the OpenSSL error stack, and returns the stack.
+fn to_c_str(name: &[u8]) -> Result<&CStr, ErrorStack> {
+    const ERR_R_PASSED_INVALID_ARGUMENT: c_int = 262 | (0x2 << 18);
+    CStr::from_bytes_with_nul(name)
+        .map_err(|_| evp_error(ERR_R_PASSED_INVALID_ARGUMENT, c"to_c_str"))
+}
+
 /// Extension trait for [`PkeyCtxRef`] to support key encapsulation mechanism (KEM) operations.
-pub(crate) trait PkeyCtxRefKemExt {
+pub trait PkeyCtxRefKemExt {
     /// Initializes the encapsulation operation.
     fn encapsulate_init(&self) -> Result<(), ErrorStack>;
     /// Returns the encapsulated key and the shared secret.
-    fn encapsulate_to_vec(&mut self) -> Result<(Vec<u8>, Vec<u8>), ErrorStack>;
+    ///
+    /// The shared secret is zeroized when dropped.
+    fn encapsulate_to_vec(&mut self) -> Result<(Vec<u8>, Zeroizing<Vec<u8>>), ErrorStack>;
+    /// Writes the encapsulated key to `out` and the shared secret to `secret`, without allocating.
+    ///
+    /// Returns the lengths of the encapsulated key and shared secret. Fails if either buffer is too small.
+    fn encapsulate_in_place(
+        &mut self,
+        out: &mut [u8],
+        secret: &mut [u8],
+    ) -> Result<(usize, usize), ErrorStack>;
     /// Initializes the decapsulation operation.
     fn decapsulate_init(&self) -> Result<(), ErrorStack>;
     /// Returns the shared secret from the encapsulated key.
-    fn decapsulate_to_vec(&self, enc: &[u8]) -> Result<Vec<u8>, ErrorStack>;
+    ///
+    /// The shared secret is zeroized when dropped.
+    fn decapsulate_to_vec(&self, enc: &[u8]) -> Result<Zeroizing<Vec<u8>>, ErrorStack>;
+    /// Writes the shared secret from the encapsulated key to `out`, without allocating.
+    ///
+    /// Returns the length of the shared secret. Fails if `out` is too small.
+    fn decapsulate_in_place(&self, enc: &[u8], out: &mut [u8]) -> Result<usize, ErrorStack>;
 }
 
-pub(crate) trait PkeyCtxExt: Sized {
+/// Extension trait for [`PkeyCtx`] to support provider based algorithms.
+pub trait PkeyCtxExt: Sized {
     /// Creates a new [`PkeyCtx`] from the algorithm name.
     /// The algorithm name is a static, null-terminated, string that identifies the algorithm to use.
+    /// Fails if the name isn't null terminated.
     fn new_from_name(name: &'static [u8]) -> Result<Self, ErrorStack>;
+
+    /// Derives `length` bytes from a context initialized for a KDF derivation, e.g HKDF.
+    /// The label is passed to the KDF as its info parameter.
+    fn get_kdf_output(&mut self, label: &[u8], length: usize) -> Result<Vec<u8>, ErrorStack>;
+
+    /// Sets an octet string parameter on the context, e.g the `seed` parameter of a key generation context.
+    /// The key name must be a null terminated string, e.g `b"seed\0"`, otherwise this fails.
+    ///
+    /// Providers ignore parameters they don't recognise, so this fails if the parameter isn't settable on the
+    /// context rather than silently doing nothing.
+    fn set_octet_string_param(&mut self, key_name: &[u8], value: &[u8]) -> Result<(), ErrorStack>;
 }
 
-pub(crate) trait PkeyExt: Sized {
+/// Extension trait for [`PKey`] to support provider based algorithms.
+pub trait PkeyExt: Sized {
     /// Creates a new [`PKey`] from an encoded public key for the specified algorithm.
+    /// The algorithm name must be a null terminated string, e.g `b"mlkem768\0"`, otherwise this fails.
     fn from_encoded_public_key(
         encoded_public_key: &[u8],
         algorithm_name: &'static [u8],
     ) -> Result<Self, ErrorStack>;
 }
 
-pub(crate) trait PKeyRefExt {
+/// Extension trait for [`PKeyRef`] to read provider specific key parameters.
+pub trait PKeyRefExt {
     /// Returns the octet string parameter for the specified key name.
+    /// The key name must be a null terminated string, e.g `b"encoded-pub-key\0"`, otherwise this fails.
     fn get_octet_string_param(&self, key_name: &[u8]) -> Result<Vec<u8>, ErrorStack>;
+
+    /// Returns whether the key is of the specified algorithm, e.g `b"mlkem768\0"`.
+    /// Unlike [`PKeyRef::is_a`], the algorithm can be any provided by a loaded provider.
+    /// Returns false if the algorithm name isn't null terminated.
+    fn is_algorithm(&self, algorithm_name: &[u8]) -> bool;
 }
 
 impl<T> PkeyCtxRefKemExt for PkeyCtxRef<T> {
@@ -68,7 +370,7 @@ impl<T> PkeyCtxRefKemExt for PkeyCtxRef<T> {
         Ok(())
     }
 
-    fn encapsulate_to_vec(&mut self) -> Result<(Vec<u8>, Vec<u8>), ErrorStack> {
+    fn encapsulate_to_vec(&mut self) -> Result<(Vec<u8>, Zeroizing<Vec<u8>>), ErrorStack> {
         let mut out_len = 0;
         let mut secret_len = 0;
 
@@ -83,7 +385,7 @@ impl<T> PkeyCtxRefKemExt for PkeyCtxRef<T> {
         }
 
         let mut out = vec![0; out_len];
-        let mut secret = vec![0; secret_len];
+        let mut secret = Zeroizing::new(vec![0; secret_len]);
 
         unsafe {
             cvt(EVP_PKEY_encapsulate(
@@ -98,6 +400,41 @@ impl<T> PkeyCtxRefKemExt for PkeyCtxRef<T> {
         Ok((out, secret))
     }
 
+    fn encapsulate_in_place(
+        &mut self,
+        out: &mut [u8],
+        secret: &mut [u8],
+    ) -> Result<(usize, usize), ErrorStack> {
+        let mut out_len = 0;
+        let mut secret_len = 0;
+
+        unsafe {
+            cvt(EVP_PKEY_encapsulate(
+                self.as_ptr(),
+                ptr::null_mut(),
+                &mut out_len,
+                ptr::null_mut(),
+                &mut secret_len,
+            ))?;
+        }
+
+        if out.len() < out_len || secret.len() < secret_len {
+            return Err(buffer_too_small());
+        }
+
+        unsafe {
+            cvt(EVP_PKEY_encapsulate(
+                self.as_ptr(),
+                out.as_mut_ptr().cast(),
+                &mut out_len,
+                secret.as_mut_ptr().cast(),
+                &mut secret_len,
+            ))?;
+        }
+
+        Ok((out_len, secret_len))
+    }
+
     fn decapsulate_init(&self) -> Result<(), ErrorStack> {
         unsafe {
             cvt(EVP_PKEY_decapsulate_init(self.as_ptr(), ptr::null()))?;
@@ -106,7 +443,7 @@ impl<T> PkeyCtxRefKemExt for PkeyCtxRef<T> {
         Ok(())
     }
 
-    fn decapsulate_to_vec(&self, enc: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+    fn decapsulate_to_vec(&self, enc: &[u8]) -> Result<Zeroizing<Vec<u8>>, ErrorStack> {
         let mut unwrapped_len = 0;
 
         unsafe {
@@ -119,7 +456,7 @@ impl<T> PkeyCtxRefKemExt for PkeyCtxRef<T> {
             ))?;
         }
 
-        let mut unwrapped = vec![0; unwrapped_len];
+        let mut unwrapped = Zeroizing::new(vec![0; unwrapped_len]);
 
         unsafe {
             cvt(EVP_PKEY_decapsulate(
@@ -133,20 +470,110 @@ impl<T> PkeyCtxRefKemExt for PkeyCtxRef<T> {
 
         Ok(unwrapped)
     }
+
+    fn decapsulate_in_place(&self, enc: &[u8], out: &mut [u8]) -> Result<usize, ErrorStack> {
+        let mut unwrapped_len = 0;
+
+        unsafe {
+            cvt(EVP_PKEY_decapsulate(
+                self.as_ptr(),
+                ptr::null_mut(),
+                &mut unwrapped_len,
+                enc.as_ptr().cast(),
+                enc.len(),
+            ))?;
+        }
+
+        if out.len() < unwrapped_len {
+            return Err(buffer_too_small());
+        }
+
+        unsafe {
+            cvt(EVP_PKEY_decapsulate(
+                self.as_ptr(),
+                out.as_mut_ptr().cast(),
+                &mut unwrapped_len,
+                enc.as_ptr().cast(),
+                enc.len(),
+            ))?;
+        }
+
+        Ok(unwrapped_len)
+    }
 }
 
 impl<T> PkeyCtxExt for PkeyCtx<T> {
     fn new_from_name(name: &'static [u8]) -> Result<Self, ErrorStack> {
+        let name = to_c_str(name)?;
         openssl_sys::init();
         unsafe {
             let ptr = cvt_p(EVP_PKEY_CTX_new_from_name(
                 ptr::null_mut(),
-                name.as_ptr().cast(),
+                name.as_ptr(),
                 ptr::null(),
             ))?;
             Ok(PkeyCtx::from_ptr(ptr))
         }
     }
+
+    fn get_kdf_output(&mut self, label: &[u8], length: usize) -> Result<Vec<u8>, ErrorStack> {
+        const OSSL_KDF_PARAM_INFO: &[u8] = b"info\0";
+        const OSSL_PKEY_PARAM_KDF_OUTLEN: &[u8] = b"kdf-outlen\0";
+
+        let mut outlen = length;
+        let mut out = vec![0; length];
+        unsafe {
+            let params = [
+                OSSL_PARAM_construct_octet_string(
+                    OSSL_KDF_PARAM_INFO.as_ptr().cast(),
+                    label.as_ptr() as *mut c_void,
+                    label.len(),
+                ),
+                OSSL_PARAM_construct_size_t(
+                    OSSL_PKEY_PARAM_KDF_OUTLEN.as_ptr().cast(),
+                    &mut outlen,
+                ),
+                OSSL_PARAM_construct_end(),
+            ];
+            cvt(EVP_PKEY_CTX_set_params(self.as_ptr(), params.as_ptr()))?;
+
+            let mut out_len = length;
+            cvt(EVP_PKEY_derive(
+                self.as_ptr(),
+                out.as_mut_ptr(),
+                &mut out_len,
+            ))?;
+            out.truncate(out_len);
+        }
+        Ok(out)
+    }
+
+    fn set_octet_string_param(&mut self, key_name: &[u8], value: &[u8]) -> Result<(), ErrorStack> {
+        const EVP_R_OPERATION_NOT_SUPPORTED_FOR_THIS_KEYTYPE: c_int = 150;
+
+        let key_name = to_c_str(key_name)?;
+        unsafe {
+            let settable = EVP_PKEY_CTX_settable_params(self.as_ptr());
+            if settable.is_null() || OSSL_PARAM_locate_const(settable, key_name.as_ptr()).is_null()
+            {
+                return Err(evp_error(
+                    EVP_R_OPERATION_NOT_SUPPORTED_FOR_THIS_KEYTYPE,
+                    c"set_octet_string_param",
+                ));
+            }
+
+            let params = [
+                OSSL_PARAM_construct_octet_string(
+                    key_name.as_ptr(),
+                    value.as_ptr() as *mut c_void,
+                    value.len(),
+                ),
+                OSSL_PARAM_construct_end(),
+            ];
+            cvt(EVP_PKEY_CTX_set_params(self.as_ptr(), params.as_ptr()))?;
+        }
+        Ok(())
+    }
 }
 
 impl PkeyExt for PKey<Public> {
@@ -171,39 +598,119 @@ impl PkeyExt for PKey<Public> {
 
 impl<T> PKeyRefExt for PKeyRef<T> {
     fn get_octet_string_param(&self, key_name: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+        let key_name = to_c_str(key_name)?;
         let mut out_len = 0;
         unsafe {
             cvt(EVP_PKEY_get_octet_string_param(
                 self.as_ptr(),
-                key_name.as_ptr().cast(),
+                key_name.as_ptr(),
                 ptr::null_mut(),
                 0,
                 &mut out_len,
-            ))
-            .unwrap();
+            ))?;
         }
 
         let mut out = vec![0; out_len];
         unsafe {
             cvt(EVP_PKEY_get_octet_string_param(
                 self.as_ptr(),
-                key_name.as_ptr().cast(),
+                key_name.as_ptr(),
                 out.as_mut_ptr(),
                 out_len,
                 &mut out_len,
-            ))
-            .unwrap();
+            ))?;
         }
         Ok(out)
     }
+
+    fn is_algorithm(&self, algorithm_name: &[u8]) -> bool {
+        let Ok(name) = CStr::from_bytes_with_nul(algorithm_name) else {
+            return false;
+        };
+        unsafe { EVP_PKEY_is_a(self.as_ptr(), name.as_ptr()) == 1 }
+    }
+}
+
+#[allow(non_camel_case_types)]
+pub(crate) enum EVP_KEM {}
+
+extern "C" {
+    pub(crate) fn EVP_KEM_do_all_provided(
+        libctx: *mut OSSL_LIB_CTX,
+        f: extern "C" fn(kem: *mut EVP_KEM, arg: *mut c_void),
+        arg: *mut c_void,
+    );
+}
+extern "C" {
+    pub(crate) fn EVP_KEM_get0_name(kem: *const EVP_KEM) -> *const c_char;
+}
+
+extern "C" {
+    pub(crate) fn OSSL_PROVIDER_do_all(
+        libctx: *mut OSSL_LIB_CTX,
+        cb: extern "C" fn(provider: *mut OSSL_PROVIDER, cbdata: *mut c_void) -> c_int,
+        cbdata: *mut c_void,
+    ) -> c_int;
+}
+extern "C" {
+    pub(crate) fn OSSL_PROVIDER_get0_name(prov: *const OSSL_PROVIDER) -> *const c_char;
+}
+extern "C" {
+    pub(crate) fn OSSL_PROVIDER_get_params(
+        prov: *const OSSL_PROVIDER,
+        params: *mut OSSL_PARAM,
+    ) -> c_int;
+}
+extern "C" {
+    pub(crate) fn OSSL_PARAM_construct_utf8_ptr(
+        key: *const c_char,
+        buf: *mut *mut c_char,
+        bsize: usize,
+    ) -> OSSL_PARAM;
+}
+
+/// The signature of an OpenSSL provider's initialization function.
+#[cfg(feature = "bundled")]
+#[allow(non_camel_case_types)]
+pub(crate) type OSSL_provider_init_fn = unsafe extern "C" fn(
+    handle: *const c_void,
+    in_: *const c_void,
+    out: *mut *const c_void,
+    provctx: *mut *mut c_void,
+) -> c_int;
+
+#[cfg(feature = "bundled")]
+extern "C" {
+    pub(crate) fn OSSL_PROVIDER_add_builtin(
+        libctx: *mut OSSL_LIB_CTX,
+        name: *const c_char,
+        init_fn: OSSL_provider_init_fn,
+    ) -> c_int;
+}
+#[cfg(feature = "bundled")]
+extern "C" {
+    /// The entry point of the statically linked oqsprovider built by the `bundled` feature.
+    pub(crate) fn oqs_provider_init(
+        handle: *const c_void,
+        in_: *const c_void,
+        out: *mut *const c_void,
+        provctx: *mut *mut c_void,
+    ) -> c_int;
 }
 
 extern "C" {
-    pub fn EVP_PKEY_encapsulate_init(ctx: *mut EVP_PKEY_CTX, params: *const OSSL_PARAM) -> c_int;
+    pub(crate) fn OSSL_PROVIDER_available(libctx: *mut OSSL_LIB_CTX, name: *const c_char) -> c_int;
 }
 
 extern "C" {
-    pub fn EVP_PKEY_encapsulate(
+    pub(crate) fn EVP_PKEY_encapsulate_init(
+        ctx: *mut EVP_PKEY_CTX,
+        params: *const OSSL_PARAM,
+    ) -> c_int;
+}
+
+extern "C" {
+    pub(crate) fn EVP_PKEY_encapsulate(
         ctx: *mut EVP_PKEY_CTX,
         wrappedkey: *mut c_uchar,
         wrappedkeylen: *mut usize,
@@ -212,10 +719,13 @@ extern "C" {
     ) -> c_int;
 }
 extern "C" {
-    pub fn EVP_PKEY_decapsulate_init(ctx: *mut EVP_PKEY_CTX, params: *const OSSL_PARAM) -> c_int;
+    pub(crate) fn EVP_PKEY_decapsulate_init(
+        ctx: *mut EVP_PKEY_CTX,
+        params: *const OSSL_PARAM,
+    ) -> c_int;
 }
 extern "C" {
-    pub fn EVP_PKEY_decapsulate(
+    pub(crate) fn EVP_PKEY_decapsulate(
         ctx: *mut EVP_PKEY_CTX,
         unwrapped: *mut c_uchar,
         unwrappedlen: *mut usize,
@@ -225,7 +735,7 @@ extern "C" {
 }
 
 extern "C" {
-    pub fn EVP_PKEY_CTX_new_from_name(
+    pub(crate) fn EVP_PKEY_CTX_new_from_name(
         libctx: *mut OSSL_LIB_CTX,
         name: *const c_char,
         propquery: *const c_char,
@@ -233,7 +743,7 @@ extern "C" {
 }
 
 extern "C" {
-    pub fn EVP_PKEY_get_octet_string_param(
+    pub(crate) fn EVP_PKEY_get_octet_string_param(
         pkey: *const EVP_PKEY,
         key_name: *const c_char,
         buf: *mut c_uchar,
@@ -242,15 +752,280 @@ extern "C" {
     ) -> c_int;
 }
 extern "C" {
-    pub fn EVP_PKEY_set1_encoded_public_key(
+    pub(crate) fn EVP_PKEY_is_a(pkey: *const EVP_PKEY, name: *const c_char) -> c_int;
+}
+extern "C" {
+    pub(crate) fn EVP_PKEY_set1_encoded_public_key(
         pkey: *mut EVP_PKEY,
         pub_: *const c_uchar,
         publen: usize,
     ) -> c_int;
 }
 extern "C" {
-    pub fn EVP_PKEY_paramgen_init(ctx: *mut EVP_PKEY_CTX) -> c_int;
+    pub(crate) fn EVP_PKEY_paramgen_init(ctx: *mut EVP_PKEY_CTX) -> c_int;
+}
+extern "C" {
+    pub(crate) fn EVP_PKEY_paramgen(ctx: *mut EVP_PKEY_CTX, ppkey: *mut *mut EVP_PKEY) -> c_int;
+}
+extern "C" {
+    pub(crate) fn EVP_PKEY_CTX_set_params(
+        ctx: *mut EVP_PKEY_CTX,
+        params: *const OSSL_PARAM,
+    ) -> c_int;
+}
+extern "C" {
+    pub(crate) fn EVP_PKEY_CTX_settable_params(ctx: *mut EVP_PKEY_CTX) -> *const OSSL_PARAM;
+}
+extern "C" {
+    pub(crate) fn EVP_PKEY_derive(
+        ctx: *mut EVP_PKEY_CTX,
+        key: *mut c_uchar,
+        keylen: *mut usize,
+    ) -> c_int;
+}
+extern "C" {
+    pub(crate) fn OSSL_PARAM_construct_octet_string(
+        key: *const c_char,
+        buf: *mut c_void,
+        bsize: usize,
+    ) -> OSSL_PARAM;
+}
+extern "C" {
+    pub(crate) fn OSSL_PARAM_construct_size_t(key: *const c_char, buf: *mut usize) -> OSSL_PARAM;
 }
 extern "C" {
-    pub fn EVP_PKEY_paramgen(ctx: *mut EVP_PKEY_CTX, ppkey: *mut *mut EVP_PKEY) -> c_int;
+    pub(crate) fn OSSL_PARAM_construct_end() -> OSSL_PARAM;
+}
+extern "C" {
+    pub(crate) fn OSSL_PARAM_locate_const(
+        params: *const OSSL_PARAM,
+        key: *const c_char,
+    ) -> *const OSSL_PARAM;
+}
+
+#[cfg(test)]
+mod tests {
+    use foreign_types::ForeignTypeRef;
+    use openssl::md::Md;
+    use openssl::pkey::{Id, PKey, Private, Public};
+    use openssl::pkey_ctx::PkeyCtx;
+    use openssl::rsa::Rsa;
+    use openssl_sys::{c_int, EVP_PKEY_CTX};
+    use std::ffi::c_char;
+    use zeroize::Zeroizing;
+
+    use super::{cvt, PKeyRefExt, PkeyCtxExt, PkeyCtxRefKemExt, PkeyExt};
+
+    extern "C" {
+        fn EVP_PKEY_CTX_set_kem_op(ctx: *mut EVP_PKEY_CTX, op: *const c_char) -> c_int;
+    }
+
+    fn set_rsasve(ctx: &PkeyCtx<impl openssl::pkey::HasPublic>) {
+        unsafe {
+            cvt(EVP_PKEY_CTX_set_kem_op(ctx.as_ptr(), c"RSASVE".as_ptr())).unwrap();
+        }
+    }
+
+    /// Generates an RSA key pair for OpenSSL's built in RSA KEM, so KEM tests don't need oqsprovider.
+    fn rsa_kem_keys() -> (PKey<Private>, PKey<Public>) {
+        let rsa = Rsa::generate(2048).unwrap();
+        let private_key = PKey::from_rsa(rsa.clone()).unwrap();
+        let public_key = PKey::from_rsa(
+            Rsa::from_public_components(rsa.n().to_owned().unwrap(), rsa.e().to_owned().unwrap())
+                .unwrap(),
+        )
+        .unwrap();
+        (private_key, public_key)
+    }
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    fn hkdf_ctx(salt: Option<&[u8]>) -> PkeyCtx<()> {
+        let mut ctx = PkeyCtx::new_id(Id::HKDF).unwrap();
+        ctx.derive_init().unwrap();
+        ctx.set_hkdf_md(Md::sha256()).unwrap();
+        ctx.set_hkdf_key(&[0x0b; 22]).unwrap();
+        if let Some(salt) = salt {
+            ctx.set_hkdf_salt(salt).unwrap();
+        }
+        ctx
+    }
+
+    #[test]
+    fn kdf_output_matches_hkdf() {
+        // RFC 5869 test case 1
+        let mut ctx = hkdf_ctx(Some(&hex("000102030405060708090a0b0c")));
+        let okm = ctx
+            .get_kdf_output(&hex("f0f1f2f3f4f5f6f7f8f9"), 42)
+            .unwrap();
+        assert_eq!(
+            okm,
+            hex("3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865")
+        );
+
+        // RFC 5869 test case 3
+        let mut ctx = hkdf_ctx(None);
+        let okm = ctx.get_kdf_output(b"", 42).unwrap();
+        assert_eq!(
+            okm,
+            hex("8da4e775a563c18f715f802a063c5a31b8a11f5c5ee1879ec3454e5f3c738d2d9d201395faa4b61a96c8")
+        );
+    }
+
+    #[test]
+    fn list_supported_kem_algorithms() {
+        let algorithms = super::list_supported_kem_algorithms().unwrap();
+        // The default provider includes an RSA KEM
+        assert!(
+            algorithms.iter().any(|name| name == "RSA"),
+            "{algorithms:?}"
+        );
+
+        crate::tests::load_providers();
+        let algorithms = super::list_supported_kem_algorithms().unwrap();
+        assert!(
+            algorithms.iter().any(|name| name == "mlkem768"),
+            "{algorithms:?}"
+        );
+    }
+
+    #[test]
+    fn is_algorithm_available() {
+        assert!(super::is_algorithm_available(b"X25519\0"));
+        assert!(!super::is_algorithm_available(b"notanalgorithm\0"));
+        assert!(!super::is_algorithm_available(b"X25519"));
+        assert!(openssl::error::ErrorStack::get().errors().is_empty());
+
+        crate::tests::load_providers();
+        assert!(super::is_algorithm_available(b"mlkem768\0"));
+    }
+
+    #[test]
+    fn is_algorithm() {
+        let private_key = PKey::generate_x25519().unwrap();
+        assert!(private_key.is_algorithm(b"X25519\0"));
+        assert!(!private_key.is_algorithm(b"X448\0"));
+        assert!(!private_key.is_algorithm(b"X25519"));
+    }
+
+    #[test]
+    fn names_must_be_null_terminated() {
+        assert!(PkeyCtx::<()>::new_from_name(b"X25519").is_err());
+        assert!(PkeyCtx::<()>::new_from_name(b"X\0X25519\0").is_err());
+        assert!(PKey::<Public>::from_encoded_public_key(&[0; 32], b"X25519").is_err());
+
+        let private_key = PKey::generate_x25519().unwrap();
+        assert!(private_key
+            .get_octet_string_param(b"encoded-pub-key")
+            .is_err());
+        assert_eq!(
+            private_key
+                .get_octet_string_param(b"encoded-pub-key\0")
+                .unwrap()
+                .len(),
+            32
+        );
+
+        let mut ctx = PkeyCtx::<()>::new_from_name(b"X25519\0").unwrap();
+        ctx.keygen_init().unwrap();
+        assert!(ctx.set_octet_string_param(b"seed", &[0; 32]).is_err());
+    }
+
+    fn is_semver_like(version: &str) -> bool {
+        let parts: Vec<&str> = version.split('.').collect();
+        parts.len() == 3
+            && parts
+                .iter()
+                .all(|part| part.split('-').next().unwrap().parse::<u32>().is_ok())
+    }
+
+    #[test]
+    fn provider_version() {
+        let _provider = openssl::provider::Provider::load(None, "default").unwrap();
+        let version = super::provider_version(c"default").unwrap();
+        assert!(is_semver_like(&version), "{version}");
+        assert_eq!(super::provider_version(c"notaprovider"), None);
+
+        crate::tests::load_providers();
+        let version = super::oqs_provider_version().unwrap();
+        assert!(is_semver_like(&version), "{version}");
+    }
+
+    #[test]
+    fn supported_kem_algorithms() {
+        crate::tests::load_providers();
+        let infos = super::supported_kem_algorithms().unwrap();
+        let mlkem768 = infos.iter().find(|info| info.name == "mlkem768").unwrap();
+        assert_eq!(mlkem768.pub_key_size, 1184);
+        assert_eq!(mlkem768.ciphertext_size, 1088);
+        assert_eq!(mlkem768.secret_size, 32);
+    }
+
+    #[test]
+    fn kem_secrets_are_zeroizing() {
+        let (private_key, public_key) = rsa_kem_keys();
+
+        let mut ctx = PkeyCtx::new(&public_key).unwrap();
+        ctx.encapsulate_init().unwrap();
+        set_rsasve(&ctx);
+        let (ciphertext, secret): (Vec<u8>, Zeroizing<Vec<u8>>) = ctx.encapsulate_to_vec().unwrap();
+
+        let ctx = PkeyCtx::new(&private_key).unwrap();
+        ctx.decapsulate_init().unwrap();
+        set_rsasve(&ctx);
+        let decapsulated: Zeroizing<Vec<u8>> = ctx.decapsulate_to_vec(&ciphertext).unwrap();
+
+        assert!(!secret.is_empty());
+        assert_eq!(secret, decapsulated);
+    }
+
+    #[test]
+    fn encapsulate_in_place() {
+        let (private_key, public_key) = rsa_kem_keys();
+
+        let mut ctx = PkeyCtx::new(&public_key).unwrap();
+        ctx.encapsulate_init().unwrap();
+        set_rsasve(&ctx);
+
+        let mut out = [0; 512];
+        let mut secret = [0; 512];
+        assert!(ctx
+            .encapsulate_in_place(&mut out[..255], &mut secret)
+            .is_err());
+        let (out_len, secret_len) = ctx.encapsulate_in_place(&mut out, &mut secret).unwrap();
+        assert_eq!(out_len, 256);
+        assert_eq!(secret_len, 256);
+
+        let ctx = PkeyCtx::new(&private_key).unwrap();
+        ctx.decapsulate_init().unwrap();
+        set_rsasve(&ctx);
+        let decapsulated = ctx.decapsulate_to_vec(&out[..out_len]).unwrap();
+        assert_eq!(decapsulated.as_slice(), &secret[..secret_len]);
+    }
+
+    #[test]
+    fn decapsulate_in_place() {
+        let (private_key, public_key) = rsa_kem_keys();
+
+        let mut ctx = PkeyCtx::new(&public_key).unwrap();
+        ctx.encapsulate_init().unwrap();
+        set_rsasve(&ctx);
+        let (ciphertext, secret) = ctx.encapsulate_to_vec().unwrap();
+
+        let ctx = PkeyCtx::new(&private_key).unwrap();
+        ctx.decapsulate_init().unwrap();
+        set_rsasve(&ctx);
+
+        let mut out = [0; 512];
+        assert!(ctx
+            .decapsulate_in_place(&ciphertext, &mut out[..255])
+            .is_err());
+        let len = ctx.decapsulate_in_place(&ciphertext, &mut out).unwrap();
+        assert_eq!(&out[..len], secret.as_slice());
+    }
 }
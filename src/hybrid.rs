@@ -0,0 +1,293 @@
+//! Generic composition of a classical and a post-quantum key exchange group.
+use openssl::md::Md;
+use openssl::pkey::Id;
+use openssl::pkey_ctx::{HkdfMode, PkeyCtx};
+use rustls::crypto::{ActiveKeyExchange, CompletedKeyExchange, SharedSecret, SupportedKxGroup};
+use rustls::{Error, NamedGroup, ProtocolVersion};
+use std::fmt::{self, Debug};
+use std::ops::Deref;
+use std::sync::Arc;
+use zeroize::Zeroizing;
+
+/// Combines the classical and post-quantum shared secrets of a [`HybridKxGroup`].
+pub trait SecretCombiner: Send + Sync {
+    /// Returns the combined shared secret.
+    fn combine(&self, classical: &[u8], post_quantum: &[u8]) -> Vec<u8>;
+}
+
+/// Concatenates the classical secret and the post-quantum secret, as the hybrid TLS groups do.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct ConcatCombiner;
+
+impl SecretCombiner for ConcatCombiner {
+    fn combine(&self, classical: &[u8], post_quantum: &[u8]) -> Vec<u8> {
+        [classical, post_quantum].concat()
+    }
+}
+
+/// Combines the secrets with HKDF-Extract using SHA-256, with the concatenated secrets as the input keying material.
+///
+/// This is the approach taken by protocols such as X-Wing and HPKE, rather than passing both secrets to the TLS
+/// key schedule.
+#[derive(Debug, Copy, Clone)]
+pub struct HkdfExtractCombiner {
+    /// The HKDF salt.
+    pub salt: &'static [u8],
+}
+
+impl SecretCombiner for HkdfExtractCombiner {
+    fn combine(&self, classical: &[u8], post_quantum: &[u8]) -> Vec<u8> {
+        let ikm = Zeroizing::new([classical, post_quantum].concat());
+        hkdf_extract(self.salt, &ikm).expect("HKDF-Extract with SHA-256 failed")
+    }
+}
+
+fn hkdf_extract(salt: &[u8], ikm: &[u8]) -> Result<Vec<u8>, openssl::error::ErrorStack> {
+    let mut ctx = PkeyCtx::new_id(Id::HKDF)?;
+    ctx.derive_init()?;
+    ctx.set_hkdf_mode(HkdfMode::EXTRACT_ONLY)?;
+    ctx.set_hkdf_md(Md::sha256())?;
+    ctx.set_hkdf_salt(salt)?;
+    ctx.set_hkdf_key(ikm)?;
+    let mut prk = Vec::new();
+    ctx.derive_to_vec(&mut prk)?;
+    Ok(prk)
+}
+
+/// A hybrid key exchange group composed of a classical group and a post-quantum group.
+///
+/// Public keys and ciphertexts are the classical component followed by the post-quantum component, and the shared
+/// secret is the output of a [`SecretCombiner`]. Both components can be any [`SupportedKxGroup`], e.g X25519
+/// from another crypto provider with [`MLKEM768`](crate::MLKEM768):
+///
+/// ```no_run
+/// use rustls::crypto::SupportedKxGroup;
+/// use rustls::NamedGroup;
+/// use rustls_liboqs::{ConcatCombiner, HybridKxGroup, MLKEM768};
+///
+/// let group = HybridKxGroup::new(
+///     NamedGroup::Unknown(0xfe00),
+///     rustls::crypto::aws_lc_rs::kx_group::X25519,
+///     MLKEM768,
+///     Box::new(ConcatCombiner),
+/// );
+/// let kx = group.start().unwrap();
+/// ```
+///
+/// The classical group's public keys must all be the same length, as is the case for ECDHE groups, so that the
+/// components can be split.
+pub struct HybridKxGroup<C, P> {
+    named_group: NamedGroup,
+    classical: C,
+    post_quantum: P,
+    combiner: Arc<dyn SecretCombiner>,
+}
+
+impl<C: Debug, P: Debug> Debug for HybridKxGroup<C, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HybridKxGroup")
+            .field("named_group", &self.named_group)
+            .field("classical", &self.classical)
+            .field("post_quantum", &self.post_quantum)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<C, P> HybridKxGroup<C, P>
+where
+    C: Deref + Send + Sync + Debug + 'static,
+    C::Target: SupportedKxGroup,
+    P: Deref + Send + Sync + Debug + 'static,
+    P::Target: SupportedKxGroup,
+{
+    /// Composes `classical` and `post_quantum` into a hybrid group, identified in TLS by `named_group`.
+    pub fn new(
+        named_group: NamedGroup,
+        classical: C,
+        post_quantum: P,
+        combiner: Box<dyn SecretCombiner>,
+    ) -> Self {
+        Self {
+            named_group,
+            classical,
+            post_quantum,
+            combiner: combiner.into(),
+        }
+    }
+
+    fn split<'a>(
+        &self,
+        bytes: &'a [u8],
+        classical_len: usize,
+    ) -> Result<(&'a [u8], &'a [u8]), Error> {
+        bytes.split_at_checked(classical_len).ok_or_else(|| {
+            Error::General(format!(
+                "{:?} key share of {} bytes is shorter than the classical component",
+                self.named_group,
+                bytes.len()
+            ))
+        })
+    }
+}
+
+impl<C, P> SupportedKxGroup for HybridKxGroup<C, P>
+where
+    C: Deref + Send + Sync + Debug + 'static,
+    C::Target: SupportedKxGroup,
+    P: Deref + Send + Sync + Debug + 'static,
+    P::Target: SupportedKxGroup,
+{
+    fn start(&self) -> Result<Box<dyn ActiveKeyExchange>, Error> {
+        let classical = self.classical.start()?;
+        let post_quantum = self.post_quantum.start()?;
+        let pub_key = [classical.pub_key(), post_quantum.pub_key()].concat();
+        Ok(Box::new(HybridKeyExchange {
+            named_group: self.named_group,
+            classical,
+            post_quantum,
+            combiner: self.combiner.clone(),
+            pub_key,
+        }))
+    }
+
+    fn start_and_complete(&self, peer_pub_key: &[u8]) -> Result<CompletedKeyExchange, Error> {
+        let classical = self.classical.start()?;
+        let (classical_pub_key, pq_pub_key) =
+            self.split(peer_pub_key, classical.pub_key().len())?;
+
+        let pub_key = classical.pub_key().to_vec();
+        let classical_secret = classical.complete(classical_pub_key)?;
+        let post_quantum = self.post_quantum.start_and_complete(pq_pub_key)?;
+
+        Ok(CompletedKeyExchange {
+            group: self.named_group,
+            pub_key: [pub_key, post_quantum.pub_key].concat(),
+            secret: SharedSecret::from(self.combiner.combine(
+                classical_secret.secret_bytes(),
+                post_quantum.secret.secret_bytes(),
+            )),
+        })
+    }
+
+    fn name(&self) -> NamedGroup {
+        self.named_group
+    }
+
+    fn usable_for_version(&self, version: ProtocolVersion) -> bool {
+        version == ProtocolVersion::TLSv1_3
+    }
+
+    fn ffdhe_group(&self) -> Option<rustls::ffdhe_groups::FfdheGroup<'static>> {
+        None
+    }
+}
+
+/// An in-progress key exchange for a [`HybridKxGroup`].
+struct HybridKeyExchange {
+    named_group: NamedGroup,
+    classical: Box<dyn ActiveKeyExchange>,
+    post_quantum: Box<dyn ActiveKeyExchange>,
+    combiner: Arc<dyn SecretCombiner>,
+    pub_key: Vec<u8>,
+}
+
+impl ActiveKeyExchange for HybridKeyExchange {
+    fn complete(self: Box<Self>, peer_pub_key: &[u8]) -> Result<SharedSecret, Error> {
+        let classical_len = self.classical.pub_key().len();
+        let (classical_ciphertext, pq_ciphertext) = peer_pub_key
+            .split_at_checked(classical_len)
+            .ok_or_else(|| {
+                Error::General(format!(
+                    "{:?} key share of {} bytes is shorter than the classical component",
+                    self.named_group,
+                    peer_pub_key.len()
+                ))
+            })?;
+
+        let classical_secret = self.classical.complete(classical_ciphertext)?;
+        let pq_secret = self.post_quantum.complete(pq_ciphertext)?;
+        Ok(SharedSecret::from(self.combiner.combine(
+            classical_secret.secret_bytes(),
+            pq_secret.secret_bytes(),
+        )))
+    }
+
+    fn pub_key(&self) -> &[u8] {
+        &self.pub_key
+    }
+
+    fn group(&self) -> NamedGroup {
+        self.named_group
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ConcatCombiner, HkdfExtractCombiner, HybridKxGroup, SecretCombiner};
+    use crate::tests::roundtrip;
+    use crate::MLKEM768;
+    use rustls::crypto::SupportedKxGroup;
+    use rustls::NamedGroup;
+
+    #[test]
+    fn x25519_mlkem768_roundtrip() {
+        let combiners: [Box<dyn SecretCombiner>; 2] = [
+            Box::new(ConcatCombiner),
+            Box::new(HkdfExtractCombiner { salt: b"salt" }),
+        ];
+        for combiner in combiners {
+            let group = HybridKxGroup::new(
+                NamedGroup::Unknown(0xfe00),
+                rustls_openssl::kx_group::X25519,
+                MLKEM768,
+                combiner,
+            );
+            roundtrip(&group, &group);
+        }
+    }
+
+    #[test]
+    fn combiners() {
+        let classical = [1; 32];
+        let post_quantum = [2; 48];
+
+        let concatenated = ConcatCombiner.combine(&classical, &post_quantum);
+        assert_eq!(concatenated.len(), 80);
+        assert_eq!(concatenated, [&classical[..], &post_quantum[..]].concat());
+
+        let extracted = HkdfExtractCombiner { salt: b"salt" }.combine(&classical, &post_quantum);
+        assert_eq!(extracted.len(), 32);
+        assert!(extracted.iter().any(|&b| b != 0));
+        assert!(!concatenated
+            .windows(extracted.len())
+            .any(|window| window == extracted));
+        assert_ne!(
+            extracted,
+            HkdfExtractCombiner { salt: b"other" }.combine(&classical, &post_quantum)
+        );
+
+        // RFC 5869 test case 1, with the IKM split between the components
+        let combiner = HkdfExtractCombiner {
+            salt: &[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12],
+        };
+        assert_eq!(
+            combiner.combine(&[0x0b; 11], &[0x0b; 11]),
+            [
+                0x07, 0x77, 0x09, 0x36, 0x2c, 0x2e, 0x32, 0xdf, 0x0d, 0xdc, 0x3f, 0x0d, 0xc4, 0x7b,
+                0xba, 0x63, 0x90, 0xb6, 0xc7, 0x3b, 0xb5, 0x0f, 0x9c, 0x31, 0x22, 0xec, 0x84, 0x4a,
+                0xd7, 0xc2, 0xb3, 0xe5,
+            ]
+        );
+    }
+
+    #[test]
+    fn short_key_share() {
+        let group = HybridKxGroup::new(
+            NamedGroup::Unknown(0xfe00),
+            rustls_openssl::kx_group::X25519,
+            MLKEM768,
+            Box::new(ConcatCombiner),
+        );
+        assert!(group.start_and_complete(&[0; 16]).is_err());
+    }
+}
@@ -1,22 +1,141 @@
 //! Key Encapsulation Mechanism (KEM) key exchange groups.
-use crate::ffi::{PKeyRefExt, PkeyCtxExt, PkeyCtxRefKemExt, PkeyExt};
+use crate::error::KemError;
+use crate::ffi::{self, PKeyRefExt, PkeyCtxExt, PkeyCtxRefKemExt, PkeyExt};
+use crate::observe::{observe, Operation};
+use crate::provider::check_oqs_provider_available;
+use crate::xwing::X_WING;
+use openssl::bn::BigNumContext;
 use openssl::derive::Deriver;
-use openssl::pkey::{Id, PKey, Private};
+use openssl::ec::{EcGroup, EcKey, EcPoint};
+use openssl::error::ErrorStack;
+use openssl::hash::MessageDigest;
+use openssl::nid::Nid;
+use openssl::pkey::{Id, PKey, Private, Public};
 use openssl::pkey_ctx::PkeyCtx;
+use openssl::sign::Signer;
 use rustls::crypto::{ActiveKeyExchange, CompletedKeyExchange, SharedSecret, SupportedKxGroup};
 use rustls::{Error, NamedGroup, ProtocolVersion};
-use zeroize::Zeroize;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::thread;
+use std::time::{Duration, Instant};
+use subtle::ConstantTimeEq;
+use zeroize::{Zeroize, Zeroizing};
+
+/// This is the [MLKEM] key exchange, using the ML-KEM-512 parameter set.
+///
+/// [MLKEM]: https://datatracker.ietf.org/doc/draft-connolly-tls-mlkem-key-agreement
+pub const MLKEM512: &dyn SupportedKxGroup = &KxGroup::MLKEM512;
 
 /// This is the [MLKEM] key exchange.
 ///
 /// [MLKEM]: https://datatracker.ietf.org/doc/draft-connolly-tls-mlkem-key-agreement
-pub const MLKEM768: &dyn SupportedKxGroup = &KxGroup::new(NamedGroup::MLKEM768, b"mlkem768\0");
+pub const MLKEM768: &dyn SupportedKxGroup = &KxGroup::MLKEM768;
+
+/// This is the [MLKEM] key exchange, using the ML-KEM-1024 parameter set.
+///
+/// [MLKEM]: https://datatracker.ietf.org/doc/draft-connolly-tls-mlkem-key-agreement
+pub const MLKEM1024: &dyn SupportedKxGroup = &KxGroup::MLKEM1024;
+
+/// This is the [SecP256r1MLKEM768] key exchange.
+///
+/// [SecP256r1MLKEM768]: <https://datatracker.ietf.org/doc/draft-kwiatkowski-tls-ecdhe-mlkem/>
+pub const P256MLKEM768: &dyn SupportedKxGroup = &KxGroup::P256MLKEM768;
+
+/// This is the [SecP384r1MLKEM1024] key exchange.
+///
+/// [SecP384r1MLKEM1024]: <https://datatracker.ietf.org/doc/draft-kwiatkowski-tls-ecdhe-mlkem/>
+pub const P384MLKEM1024: &dyn SupportedKxGroup = &KxGroup::P384MLKEM1024;
+
+/// This is the X448MLKEM1024 key exchange, combining X448 with ML-KEM-1024.
+///
+/// There is no IANA codepoint for this group, so it uses the private use codepoint `0xfe70`.
+pub const X448MLKEM1024: &dyn SupportedKxGroup = &KxGroup::X448MLKEM1024;
+
+/// This is the [HQC] key exchange, using the HQC-128 parameter set.
+///
+/// HQC is a code based KEM, so offers an alternative to the lattice based ML-KEM.
+/// There is no IANA codepoint for this group, so it uses the private use codepoint `0xfe30`.
+///
+/// [HQC]: <https://pqc-hqc.org/>
+pub const HQC128: &dyn SupportedKxGroup = &KxGroup::HQC128;
+
+/// This is the [BIKE] key exchange, using the BIKE-L1 parameter set.
+///
+/// BIKE is a code based KEM, with larger public keys and ciphertexts than ML-KEM.
+/// There is no IANA codepoint for this group, so it uses the private use codepoint `0xfe40`.
+///
+/// [BIKE]: <https://bikesuite.org/>
+pub const BIKEL1: &dyn SupportedKxGroup = &KxGroup::BIKEL1;
+
+/// This is the [FrodoKEM] key exchange, using the FrodoKEM-640-AES parameter set.
+///
+/// FrodoKEM's security relies on plain LWE rather than structured lattices, making it a conservative choice at the
+/// cost of large (9616 byte) public keys.
+/// There is no IANA codepoint for this group, so it uses the private use codepoint `0xfe50`.
+///
+/// [FrodoKEM]: <https://frodokem.org/>
+pub const FRODO640AES: &dyn SupportedKxGroup = &KxGroup::FRODO640AES;
+
+/// This is the pre-standardization Kyber768 key exchange.
+///
+/// Kyber768 is not compatible with the standardized ML-KEM-768, and is only provided to aid migration from
+/// deployments that still use it.
+/// There is no IANA codepoint for this group, so it uses the private use codepoint `0xfe60`.
+#[deprecated(note = "Use MLKEM768 instead")]
+#[allow(deprecated)]
+pub const KYBER768: &dyn SupportedKxGroup = &KxGroup::KYBER768;
 
 /// This is the [X25519MLKEM768] key exchange.
 ///
 /// [X25519MLKEM768]: <https://datatracker.ietf.org/doc/draft-kwiatkowski-tls-ecdhe-mlkem/>
-pub const X25519MLKEM768: &dyn SupportedKxGroup =
-    &KxGroup::new(NamedGroup::X25519MLKEM768, b"X25519MLKEM768\0");
+pub const X25519MLKEM768: &dyn SupportedKxGroup = &KxGroup::X25519MLKEM768;
+
+/// The SecP384r1MLKEM1024 codepoint, which rustls doesn't define yet.
+const SECP384R1_MLKEM1024: NamedGroup = NamedGroup::Unknown(0x11ed);
+
+/// A private use codepoint for X448MLKEM1024, which has no IANA assignment.
+const X448_MLKEM1024: NamedGroup = NamedGroup::Unknown(0xfe70);
+
+/// A private use codepoint for HQC-128, which has no IANA assignment.
+const HQC_128: NamedGroup = NamedGroup::Unknown(0xfe30);
+
+/// A private use codepoint for BIKE-L1, which has no IANA assignment.
+const BIKE_L1: NamedGroup = NamedGroup::Unknown(0xfe40);
+
+/// A private use codepoint for FrodoKEM-640-AES, which has no IANA assignment.
+const FRODO_640_AES: NamedGroup = NamedGroup::Unknown(0xfe50);
+
+/// A private use codepoint for pure Kyber768, which has no IANA assignment.
+///
+/// The IANA codepoint `0x6399` is for the X25519Kyber768Draft00 hybrid, whose key shares aren't compatible with
+/// this group's.
+const KYBER_768: NamedGroup = NamedGroup::Unknown(0xfe60);
+
+/// The longest shared secret of the supported groups, with room to spare.
+///
+/// The longest is X448MLKEM1024's, 56 bytes of X448 secret followed by 32 bytes of ML-KEM secret.
+const MAX_SECRET_LEN: usize = 128;
+
+/// How the two peers of a key exchange group agree on a shared secret.
+///
+/// In TLS 1.3 the client always calls [`SupportedKxGroup::start`], and sends its public key in its key share.
+/// The server calls [`SupportedKxGroup::start_and_complete`] with the client's public key, and the resulting
+/// ciphertext is sent back as the server's key share. Finally the client calls [`ActiveKeyExchange::complete`]
+/// with the server's key share.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum KeyAgreementMode {
+    /// Key encapsulation only.
+    ///
+    /// The two sides are asymmetric: the client is the decapsulator and the server the encapsulator.
+    Kem,
+    /// A key encapsulation mechanism combined with an elliptic curve Diffie-Hellman (ECDH) key exchange.
+    ///
+    /// The group as a whole follows the same asymmetric flow as [`KeyAgreementMode::Kem`]. The classical
+    /// component can also be used on its own via [`ActiveKeyExchange::hybrid_component`], in which case both
+    /// sides call `start()` and `complete()` as for any ECDH group.
+    HybridKemEcdh,
+}
 
 /// A key exchange group based on a key encapsulation mechanism.
 #[derive(Debug, Copy, Clone)]
@@ -26,51 +145,646 @@ pub struct KxGroup {
 }
 
 impl KxGroup {
+    /// The [`MLKEM512`] key exchange group.
+    pub const MLKEM512: KxGroup = KxGroup::new(NamedGroup::MLKEM512, b"mlkem512\0");
+
+    /// The [`MLKEM768`] key exchange group.
+    pub const MLKEM768: KxGroup = KxGroup::new(NamedGroup::MLKEM768, b"mlkem768\0");
+
+    /// The [`MLKEM1024`] key exchange group.
+    pub const MLKEM1024: KxGroup = KxGroup::new(NamedGroup::MLKEM1024, b"mlkem1024\0");
+
+    /// The [`P256MLKEM768`] key exchange group.
+    pub const P256MLKEM768: KxGroup =
+        KxGroup::new(NamedGroup::secp256r1MLKEM768, b"p256_mlkem768\0");
+
+    /// The [`P384MLKEM1024`] key exchange group.
+    pub const P384MLKEM1024: KxGroup = KxGroup::new(SECP384R1_MLKEM1024, b"p384_mlkem1024\0");
+
+    /// The [`X448MLKEM1024`] key exchange group.
+    pub const X448MLKEM1024: KxGroup = KxGroup::new(X448_MLKEM1024, b"x448_mlkem1024\0");
+
+    /// The [`HQC128`] key exchange group.
+    pub const HQC128: KxGroup = KxGroup::new(HQC_128, b"hqc128\0");
+
+    /// The [`BIKEL1`] key exchange group.
+    pub const BIKEL1: KxGroup = KxGroup::new(BIKE_L1, b"bikel1\0");
+
+    /// The [`FRODO640AES`] key exchange group.
+    pub const FRODO640AES: KxGroup = KxGroup::new(FRODO_640_AES, b"frodo640aes\0");
+
+    /// The [`KYBER768`] key exchange group.
+    #[deprecated(note = "Use MLKEM768 instead")]
+    pub const KYBER768: KxGroup = KxGroup::new(KYBER_768, b"kyber768\0");
+
+    /// The [`X25519MLKEM768`] key exchange group.
+    pub const X25519MLKEM768: KxGroup =
+        KxGroup::new(NamedGroup::X25519MLKEM768, b"X25519MLKEM768\0");
+
     /// Create a new key exchange group with the specified named group and OpenSSL algorithm name.
-    /// The name should be a null terminated string, e.g `b"kyber768\0"`.
+    /// The name must be a null terminated string, e.g `b"kyber768\0"`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the name isn't null terminated. When used to initialize a constant, this is a compile error.
     pub const fn new(named_group: NamedGroup, algorithm_name: &'static [u8]) -> Self {
         Self {
             named_group,
-            algorithm_name,
+            algorithm_name: validate_algorithm_name(algorithm_name),
+        }
+    }
+
+    /// Returns a copy of this group that uses a different OpenSSL algorithm name.
+    ///
+    /// The [`NamedGroup`] is unchanged, so this is useful when a provider registers the same algorithm
+    /// under another name. The name must be a null terminated string, e.g `b"MLKEM768\0"`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the name isn't null terminated, as for [`KxGroup::new`].
+    pub const fn with_custom_algorithm_name(&self, name: &'static [u8]) -> KxGroup {
+        Self {
+            named_group: self.named_group,
+            algorithm_name: validate_algorithm_name(name),
+        }
+    }
+
+    /// Returns the post-quantum KEM used by this group.
+    ///
+    /// For hybrid groups this is the ML-KEM component, e.g [`NamedGroup::MLKEM768`] for
+    /// [`NamedGroup::X25519MLKEM768`]. Pure KEM groups return their own [`NamedGroup`].
+    pub fn post_quantum_component_group(&self) -> NamedGroup {
+        match self.named_group {
+            NamedGroup::X25519MLKEM768 | NamedGroup::secp256r1MLKEM768 => NamedGroup::MLKEM768,
+            SECP384R1_MLKEM1024 | X448_MLKEM1024 => NamedGroup::MLKEM1024,
+            named_group => named_group,
+        }
+    }
+
+    /// Returns the OpenSSL algorithm name of the post-quantum KEM used by this group, e.g `mlkem768`.
+    ///
+    /// For hybrid groups this is the name of the ML-KEM component, e.g `mlkem768` for `X25519MLKEM768`. Pure KEM
+    /// groups return their own algorithm name, or an empty string if it isn't valid UTF-8.
+    pub fn kem_component_name(&self) -> &'static str {
+        match self.named_group {
+            NamedGroup::X25519MLKEM768 | NamedGroup::secp256r1MLKEM768 => "mlkem768",
+            SECP384R1_MLKEM1024 | X448_MLKEM1024 => "mlkem1024",
+            _ => {
+                let name = self
+                    .algorithm_name
+                    .strip_suffix(b"\0")
+                    .unwrap_or(self.algorithm_name);
+                std::str::from_utf8(name).unwrap_or_default()
+            }
+        }
+    }
+
+    /// Returns whether this is a hybrid group, combining a post-quantum KEM with a classical key exchange.
+    pub fn is_hybrid(&self) -> bool {
+        self.classical_component().is_some()
+    }
+
+    /// Returns the classical key exchange group of a hybrid group.
+    ///
+    /// For example, this is [`NamedGroup::X25519`] for [`NamedGroup::X25519MLKEM768`]. Pure KEM groups return `None`.
+    pub fn classical_component(&self) -> Option<NamedGroup> {
+        match self.named_group {
+            NamedGroup::X25519MLKEM768 => Some(NamedGroup::X25519),
+            NamedGroup::secp256r1MLKEM768 => Some(NamedGroup::secp256r1),
+            SECP384R1_MLKEM1024 => Some(NamedGroup::secp384r1),
+            X448_MLKEM1024 => Some(NamedGroup::X448),
+            _ => None,
+        }
+    }
+
+    /// Returns the post-quantum security level of this group in bits.
+    ///
+    /// This is 128, 192 or 256 for NIST security categories 1, 3 and 5 respectively, or 0 for unknown groups.
+    /// For hybrid groups this is the security level of the post-quantum component.
+    pub fn security_bits(&self) -> u32 {
+        security_bits(self.named_group)
+    }
+
+    /// Returns the TLS NamedGroup codepoint of this group, as sent on the wire, e.g `0x0201` for ML-KEM-768.
+    pub fn to_wire_id(&self) -> u16 {
+        u16::from(self.named_group)
+    }
+
+    /// Returns the key exchange group provided by this crate with the TLS NamedGroup codepoint `id`, if any.
+    ///
+    /// See [`kx_group_by_named_group`](crate::kx_group_by_named_group).
+    pub fn from_wire_id(id: u16) -> Option<&'static dyn SupportedKxGroup> {
+        crate::kx_group_by_named_group(NamedGroup::from(id))
+    }
+
+    /// Returns the group provided by this crate for the named group, if any.
+    ///
+    /// Unlike [`kx_group_by_named_group`](crate::kx_group_by_named_group) this returns a [`KxGroup`], so its
+    /// methods can be called, e.g [`KxGroup::security_bits`]. X-Wing isn't a [`KxGroup`], so returns `None`.
+    pub fn from_named_group(group: NamedGroup) -> Option<KxGroup> {
+        #[allow(deprecated)]
+        [
+            KxGroup::MLKEM512,
+            KxGroup::MLKEM768,
+            KxGroup::MLKEM1024,
+            KxGroup::X25519MLKEM768,
+            KxGroup::P256MLKEM768,
+            KxGroup::P384MLKEM1024,
+            KxGroup::X448MLKEM1024,
+            KxGroup::HQC128,
+            KxGroup::BIKEL1,
+            KxGroup::FRODO640AES,
+            KxGroup::KYBER768,
+        ]
+        .into_iter()
+        .find(|kx_group| kx_group.named_group == group)
+    }
+
+    /// Returns a description of this group, e.g to include in configuration files or API responses.
+    pub fn to_metadata(&self) -> KxGroupMetadata {
+        KxGroupMetadata {
+            name: self.to_string(),
+            named_group_id: self.to_wire_id(),
+            is_hybrid: self.is_hybrid(),
+            security_bits: self.security_bits(),
+        }
+    }
+
+    /// Returns the classical security level of the classical component of a hybrid group in bits.
+    ///
+    /// Pure KEM groups return `None`.
+    pub fn classical_security_bits(&self) -> Option<u32> {
+        match self.classical_component()? {
+            NamedGroup::X25519 | NamedGroup::secp256r1 => Some(128),
+            NamedGroup::secp384r1 => Some(192),
+            NamedGroup::X448 => Some(224),
+            _ => None,
+        }
+    }
+
+    /// Returns the size in bytes of public keys for this group, or `None` for unknown groups.
+    pub fn pub_key_size(&self) -> Option<usize> {
+        let size = match self.named_group {
+            NamedGroup::MLKEM512 => 800,
+            NamedGroup::MLKEM768 | KYBER_768 => 1184,
+            NamedGroup::MLKEM1024 => 1568,
+            NamedGroup::X25519MLKEM768 => 32 + 1184,
+            NamedGroup::secp256r1MLKEM768 => 65 + 1184,
+            SECP384R1_MLKEM1024 => 97 + 1568,
+            X448_MLKEM1024 => 56 + 1568,
+            HQC_128 => 2249,
+            BIKE_L1 => 1541,
+            FRODO_640_AES => 9616,
+            _ => return None,
+        };
+        Some(size)
+    }
+
+    /// Returns the size in bytes of ciphertexts for this group, or `None` for unknown groups.
+    pub fn ciphertext_size(&self) -> Option<usize> {
+        let size = match self.named_group {
+            NamedGroup::MLKEM512 => 768,
+            NamedGroup::MLKEM768 | KYBER_768 => 1088,
+            NamedGroup::MLKEM1024 => 1568,
+            NamedGroup::X25519MLKEM768 => 32 + 1088,
+            NamedGroup::secp256r1MLKEM768 => 65 + 1088,
+            SECP384R1_MLKEM1024 => 97 + 1568,
+            X448_MLKEM1024 => 56 + 1568,
+            HQC_128 => 4433,
+            BIKE_L1 => 1573,
+            FRODO_640_AES => 9720,
+            _ => return None,
+        };
+        Some(size)
+    }
+
+    /// Returns the size in bytes of shared secrets for this group, or `None` for unknown groups.
+    ///
+    /// Hybrid shared secrets are the concatenation of the classical and ML-KEM shared secrets.
+    pub fn shared_secret_size(&self) -> Option<usize> {
+        let size = match self.named_group {
+            NamedGroup::MLKEM512 | NamedGroup::MLKEM768 | NamedGroup::MLKEM1024 | KYBER_768 => 32,
+            NamedGroup::X25519MLKEM768 => 32 + 32,
+            NamedGroup::secp256r1MLKEM768 => 32 + 32,
+            SECP384R1_MLKEM1024 => 48 + 32,
+            X448_MLKEM1024 => 56 + 32,
+            HQC_128 => 64,
+            BIKE_L1 => 32,
+            FRODO_640_AES => 16,
+            _ => return None,
+        };
+        Some(size)
+    }
+
+    /// Checks a peer's public key has the expected length, if known.
+    fn check_pub_key_len(&self, pub_key: &[u8]) -> Result<(), KemError> {
+        match self.pub_key_size() {
+            Some(expected_len) if pub_key.len() != expected_len => {
+                Err(KemError::InvalidPublicKey {
+                    algorithm: self.algorithm(),
+                    expected_len: Some(expected_len),
+                    got_len: pub_key.len(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks a peer's ciphertext has the expected length, if known.
+    fn check_ciphertext_len(&self, ciphertext: &[u8]) -> Result<(), KemError> {
+        match self.ciphertext_size() {
+            Some(expected_len) if ciphertext.len() != expected_len => {
+                Err(KemError::InvalidCiphertext {
+                    algorithm: self.algorithm(),
+                    expected_len,
+                    got_len: ciphertext.len(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns how the peers of this group agree on a shared secret.
+    pub fn key_agreement_mode(&self) -> KeyAgreementMode {
+        match self.classical_component() {
+            Some(_) => KeyAgreementMode::HybridKemEcdh,
+            None => KeyAgreementMode::Kem,
+        }
+    }
+
+    /// Returns whether public keys for this group are prefixed with a 4-byte length header.
+    ///
+    /// oqs-provider versions before 0.7.0 use a legacy encoding for hybrid keys, which prepends the big-endian
+    /// length of the rest of the key to public keys read from the provider. The build script sets the
+    /// `swapx25519hybrid` cfg when building against these versions. Pure KEM groups never have a length prefix.
+    ///
+    /// The prefix isn't part of TLS key shares, so isn't included in [`KxGroup::pub_key_size`] or
+    /// [`KxGroup::ciphertext_size`], and must be stripped with [`KxGroup::strip_length_prefix`] before a key read
+    /// from the provider is compared with a key share.
+    pub fn pub_key_has_length_prefix(&self) -> bool {
+        self.key_agreement_mode() == KeyAgreementMode::HybridKemEcdh && cfg!(swapx25519hybrid)
+    }
+
+    /// Strips the length prefix from a public key, if this group has one.
+    ///
+    /// The prefix must equal the length of the remaining key.
+    pub fn strip_length_prefix<'a>(&self, pub_key: &'a [u8]) -> Result<&'a [u8], KemError> {
+        if !self.pub_key_has_length_prefix() {
+            return Ok(pub_key);
+        }
+
+        match pub_key.split_first_chunk::<4>() {
+            Some((prefix, rest)) if u32::from_be_bytes(*prefix) as usize == rest.len() => Ok(rest),
+            _ => Err(KemError::InvalidPublicKey {
+                algorithm: self.algorithm(),
+                expected_len: None,
+                got_len: pub_key.len(),
+            }),
+        }
+    }
+
+    /// Generates a new key pair, returning the concrete [`KeyExchange`] rather than a trait object.
+    ///
+    /// Like [`SupportedKxGroup::start`], the result must be used, or the key pair is generated for nothing:
+    ///
+    /// ```compile_fail
+    /// #![deny(unused_must_use)]
+    /// rustls_liboqs::KxGroup::MLKEM768.start_key_exchange();
+    /// ```
+    #[must_use = "key exchange will not complete if this result is dropped"]
+    pub fn start_key_exchange(&self) -> Result<Box<KeyExchange>, Error> {
+        observe(Operation::Keygen, self, || {
+            KeyExchange::generate(*self)
+                .map(Box::new)
+                .map_err(|source| {
+                    // The most likely cause of failure is that oqsprovider isn't loaded
+                    match check_oqs_provider_available() {
+                        Err(err) => err,
+                        Ok(()) => KemError::Keygen {
+                            algorithm: self.algorithm(),
+                            source,
+                        },
+                    }
+                    .into()
+                })
+        })
+    }
+
+    /// Deterministically generates a key pair from `seed`.
+    ///
+    /// **This is not suitable for production use.** It exists so tests and benchmarks can reproduce key pairs;
+    /// real key exchanges must use fresh randomness from [`SupportedKxGroup::start`].
+    ///
+    /// The seed is expanded with SHA-512 into the 64 byte `d || z` seed of [FIPS 203] key generation. The
+    /// provider must support the `seed` key generation parameter, as OpenSSL 3.5's ML-KEM implementation does;
+    /// otherwise an error is returned rather than a randomly generated key.
+    ///
+    /// [FIPS 203]: https://csrc.nist.gov/pubs/fips/203/final
+    #[cfg(any(test, feature = "testing"))]
+    #[must_use = "key exchange will not complete if this result is dropped"]
+    pub fn start_with_seed(&self, seed: &[u8; 32]) -> Result<Box<dyn ActiveKeyExchange>, Error> {
+        const OSSL_PKEY_PARAM_ML_KEM_SEED: &[u8] = b"seed\0";
+
+        let seed = Zeroizing::new(openssl::sha::sha512(seed));
+        KeyExchange::generate_with(*self, |ctx| {
+            ctx.set_octet_string_param(OSSL_PKEY_PARAM_ML_KEM_SEED, seed.as_ref())
+        })
+        .map(|kx| Box::new(kx) as Box<dyn ActiveKeyExchange>)
+        .map_err(|source| {
+            KemError::Keygen {
+                algorithm: self.algorithm(),
+                source,
+            }
+            .into()
+        })
+    }
+
+    /// Returns the null terminated OpenSSL algorithm name.
+    pub(crate) fn algorithm_name(&self) -> &'static [u8] {
+        self.algorithm_name
+    }
+
+    /// Returns the OpenSSL algorithm name, without the null terminator.
+    pub(crate) fn algorithm(&self) -> String {
+        let name = self
+            .algorithm_name
+            .strip_suffix(b"\0")
+            .unwrap_or(self.algorithm_name);
+        String::from_utf8_lossy(name).into_owned()
+    }
+
+    /// Checks that OpenSSL's random number generator has been seeded with enough entropy.
+    ///
+    /// On newly booted systems the kernel entropy pool may not yet be seeded, in which case key generation
+    /// may fail.
+    pub fn check_minimum_randomness_available() -> Result<(), KemError> {
+        if ffi::rand_status() {
+            Ok(())
+        } else {
+            Err(KemError::InsufficientEntropy)
+        }
+    }
+
+    /// Waits until OpenSSL's random number generator has enough entropy, polling every 100ms.
+    ///
+    /// Returns [`KemError::InsufficientEntropy`] if there still isn't enough entropy after `timeout`.
+    pub fn wait_for_entropy(timeout: Duration) -> Result<(), KemError> {
+        const POLL_INTERVAL: Duration = Duration::from_millis(100);
+        let deadline = Instant::now() + timeout;
+        loop {
+            match Self::check_minimum_randomness_available() {
+                Err(KemError::InsufficientEntropy) if Instant::now() < deadline => {
+                    thread::sleep(POLL_INTERVAL.min(deadline - Instant::now()));
+                }
+                result => return result,
+            }
         }
     }
 }
 
-struct KeyExchange {
+/// An in-progress key exchange for a [`KxGroup`].
+pub struct KeyExchange {
     priv_key: PKey<Private>,
     pub_key: Vec<u8>,
     mlkem: KxGroup,
     classical_pub_key: Option<Vec<u8>>,
 }
 
-impl SupportedKxGroup for KxGroup {
-    fn start(&self) -> Result<Box<(dyn ActiveKeyExchange)>, Error> {
-        PkeyCtx::<()>::new_from_name(self.algorithm_name)
-            .and_then(|mut pkey_ctx| {
-                pkey_ctx.keygen_init()?;
-                let priv_key = pkey_ctx.keygen()?;
-
-                // Don't use raw_public_key_bytes, as get octet string doesn't add classical length header for hybrid keys
-                // https://github.com/open-quantum-safe/oqs-provider/issues/572
-                const OSSL_PKEY_PARAM_ENCODED_PUB_KEY: &[u8] = b"encoded-pub-key\0";
-                let pub_key = priv_key.get_octet_string_param(OSSL_PKEY_PARAM_ENCODED_PUB_KEY)?;
-
-                let classical_pub_key = if self.named_group == NamedGroup::X25519MLKEM768 {
-                    const OQS_HYBRID_PKEY_PARAM_CLASSICAL_PUB_KEY: &[u8] =
-                        b"hybrid_classical_pub\0";
-                    Some(priv_key.get_octet_string_param(OQS_HYBRID_PKEY_PARAM_CLASSICAL_PUB_KEY)?)
-                } else {
-                    None
-                };
-
-                Ok(Box::new(KeyExchange {
-                    priv_key,
-                    pub_key,
-                    mlkem: *self,
-                    classical_pub_key,
-                }) as Box<dyn ActiveKeyExchange>)
+// Only the group and public key length are shown, so the private key can't leak into logs.
+impl fmt::Debug for KeyExchange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyExchange")
+            .field("group", &self.mlkem.named_group)
+            .field("pub_key_len", &self.pub_key.len())
+            .finish()
+    }
+}
+
+impl KeyExchange {
+    /// Generates a new key pair for the group.
+    pub(crate) fn generate(group: KxGroup) -> Result<Self, ErrorStack> {
+        Self::generate_with(group, |_| Ok(()))
+    }
+
+    /// Generates a new key pair for the group, calling `configure` on the initialized key generation context.
+    fn generate_with(
+        group: KxGroup,
+        configure: impl FnOnce(&mut PkeyCtx<()>) -> Result<(), ErrorStack>,
+    ) -> Result<Self, ErrorStack> {
+        let mut pkey_ctx = PkeyCtx::<()>::new_from_name(group.algorithm_name)?;
+        pkey_ctx.keygen_init()?;
+        configure(&mut pkey_ctx)?;
+        Self::from_private_key(pkey_ctx.keygen()?, group)
+    }
+
+    /// Creates a key exchange from a private key, reading the encoded public key from it.
+    fn from_private_key(priv_key: PKey<Private>, group: KxGroup) -> Result<Self, ErrorStack> {
+        // Don't use raw_public_key_bytes, as get octet string doesn't add classical length header for hybrid keys
+        // https://github.com/open-quantum-safe/oqs-provider/issues/572
+        const OSSL_PKEY_PARAM_ENCODED_PUB_KEY: &[u8] = b"encoded-pub-key\0";
+        let pub_key = priv_key.get_octet_string_param(OSSL_PKEY_PARAM_ENCODED_PUB_KEY)?;
+
+        Self::from_parts(priv_key, pub_key, group)
+    }
+
+    fn from_parts(
+        priv_key: PKey<Private>,
+        pub_key: Vec<u8>,
+        group: KxGroup,
+    ) -> Result<Self, ErrorStack> {
+        let classical_pub_key = if group.classical_component().is_some() {
+            const OQS_HYBRID_PKEY_PARAM_CLASSICAL_PUB_KEY: &[u8] = b"hybrid_classical_pub\0";
+            Some(priv_key.get_octet_string_param(OQS_HYBRID_PKEY_PARAM_CLASSICAL_PUB_KEY)?)
+        } else {
+            None
+        };
+
+        Ok(KeyExchange {
+            priv_key,
+            pub_key,
+            mlkem: group,
+            classical_pub_key,
+        })
+    }
+
+    /// Consumes the key exchange, returning the public key, the DER encoded private key and the group.
+    ///
+    /// The private key is zeroized when the returned buffer is dropped.
+    #[allow(clippy::type_complexity)]
+    pub fn into_raw_parts(mut self) -> Result<(Vec<u8>, Zeroizing<Vec<u8>>, KxGroup), KemError> {
+        let private_key_der =
+            self.priv_key
+                .private_key_to_der()
+                .map_err(|source| KemError::InvalidPrivateKey {
+                    algorithm: self.mlkem.algorithm(),
+                    source,
+                })?;
+        let pub_key = std::mem::take(&mut self.pub_key);
+        Ok((pub_key, Zeroizing::new(private_key_der), self.mlkem))
+    }
+
+    /// Reconstructs a key exchange from the parts returned by [`KeyExchange::into_raw_parts`].
+    pub fn from_raw_parts(
+        pub_key: Vec<u8>,
+        private_key_der: Vec<u8>,
+        group: KxGroup,
+    ) -> Result<Box<Self>, KemError> {
+        let private_key_der = Zeroizing::new(private_key_der);
+        PKey::private_key_from_der(&private_key_der)
+            .and_then(|priv_key| Self::from_parts(priv_key, pub_key, group))
+            .map(Box::new)
+            .map_err(|source| KemError::InvalidPrivateKey {
+                algorithm: group.algorithm(),
+                source,
             })
-            .map_err(|e| Error::General(format!("OpenSSL keygen error: {e}")))
+    }
+
+    /// Creates a key exchange from an existing private key for the group, e.g one generated ahead of time or held
+    /// by a hardware security module's provider.
+    ///
+    /// The public key is read from the private key, as it is when [`SupportedKxGroup::start`] generates a key pair.
+    #[must_use = "key exchange will not complete if this result is dropped"]
+    pub fn from_pkey(
+        priv_key: PKey<Private>,
+        group: KxGroup,
+    ) -> Result<Box<dyn ActiveKeyExchange>, Error> {
+        Self::from_private_key(priv_key, group)
+            .map(|kx| Box::new(kx) as Box<dyn ActiveKeyExchange>)
+            .map_err(|source| {
+                KemError::InvalidPrivateKey {
+                    algorithm: group.algorithm(),
+                    source,
+                }
+                .into()
+            })
+    }
+
+    /// Decapsulates the ciphertext with our private key, returning the shared secret.
+    pub(crate) fn decapsulate(&self, ciphertext: &[u8]) -> Result<SharedSecret, Error> {
+        observe(Operation::Decapsulate, &self.mlkem, || {
+            self.mlkem.check_ciphertext_len(ciphertext)?;
+            PkeyCtx::new(&self.priv_key)
+                .and_then(|ctx| {
+                    ctx.decapsulate_init()?;
+                    let mut secret = Zeroizing::new([0; MAX_SECRET_LEN]);
+                    let len = ctx.decapsulate_in_place(ciphertext, secret.as_mut_slice())?;
+                    Ok(SharedSecret::from(&secret[..len]))
+                })
+                .map_err(|source| {
+                    KemError::Decapsulate {
+                        algorithm: self.mlkem.algorithm(),
+                        source,
+                    }
+                    .into()
+                })
+        })
+    }
+}
+
+/// Groups are identified by their [`NamedGroup`], so groups with different algorithm names compare equal.
+impl PartialEq for KxGroup {
+    fn eq(&self, other: &Self) -> bool {
+        self.named_group == other.named_group
+    }
+}
+
+impl Eq for KxGroup {}
+
+impl Hash for KxGroup {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        u16::from(self.named_group).hash(state);
+    }
+}
+
+/// Groups are ordered by [`KxGroup::security_bits`], so stronger groups are greater, and then by algorithm name.
+///
+/// Sort in reverse to put the strongest groups first:
+///
+/// ```
+/// use rustls_liboqs::KxGroup;
+///
+/// let mut groups = vec![KxGroup::MLKEM768, KxGroup::MLKEM1024, KxGroup::MLKEM512];
+/// groups.sort_by(|a, b| b.cmp(a));
+/// assert_eq!(groups, [KxGroup::MLKEM1024, KxGroup::MLKEM768, KxGroup::MLKEM512]);
+/// ```
+///
+/// To be consistent with [`PartialEq`], the algorithm name compared is that of the group this crate provides for
+/// the [`NamedGroup`], not a custom name from [`KxGroup::with_custom_algorithm_name`]. Groups this crate doesn't
+/// provide are ordered by their codepoint.
+impl Ord for KxGroup {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        let canonical_name = |group: &KxGroup| {
+            KxGroup::from_named_group(group.named_group).map(|g| g.algorithm_name)
+        };
+        self.security_bits()
+            .cmp(&other.security_bits())
+            .then_with(|| canonical_name(self).cmp(&canonical_name(other)))
+            .then_with(|| self.to_wire_id().cmp(&other.to_wire_id()))
+    }
+}
+
+impl PartialOrd for KxGroup {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// A description of a [`KxGroup`], returned by [`KxGroup::to_metadata`].
+///
+/// With the `serde` feature this can be serialized and deserialized.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KxGroupMetadata {
+    /// The OpenSSL algorithm name, e.g `mlkem768`.
+    pub name: String,
+    /// The TLS named group codepoint.
+    pub named_group_id: u16,
+    /// Whether the group combines a post-quantum KEM with a classical key exchange.
+    pub is_hybrid: bool,
+    /// The post-quantum security level in bits, see [`KxGroup::security_bits`].
+    pub security_bits: u32,
+}
+
+impl fmt::Display for KxGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = self
+            .algorithm_name
+            .strip_suffix(b"\0")
+            .unwrap_or(self.algorithm_name);
+        match std::str::from_utf8(name) {
+            Ok(name) => f.write_str(name),
+            Err(_) => write!(f, "{:?}", self.named_group),
+        }
+    }
+}
+
+/// Zeroizes the key buffers when the key exchange is dropped, even if it was never completed.
+///
+/// The private key is an `EVP_PKEY` owned by OpenSSL, so isn't byte-accessible here. Private key material read
+/// from it, such as the classical private key of a hybrid group or the DER encoding returned by
+/// [`KeyExchange::into_raw_parts`], is only ever held in [`Zeroizing`] buffers.
+impl Drop for KeyExchange {
+    fn drop(&mut self) {
+        self.zeroize_buffers();
+        // `priv_key` is dropped after this, and relies on `EVP_PKEY_free` to cleanse the private key, which
+        // OpenSSL and oqsprovider do when freeing key material.
+    }
+}
+
+impl KeyExchange {
+    /// Zeroizes the buffers we own, without freeing them.
+    fn zeroize_buffers(&mut self) {
+        self.pub_key.zeroize();
+        if let Some(classical_pub_key) = self.classical_pub_key.as_mut() {
+            classical_pub_key.zeroize();
+        }
+    }
+}
+
+impl SupportedKxGroup for KxGroup {
+    fn start(&self) -> Result<Box<dyn ActiveKeyExchange>, Error> {
+        self.start_key_exchange()
+            .map(|kx| kx as Box<dyn ActiveKeyExchange>)
     }
 
     fn name(&self) -> NamedGroup {
@@ -89,30 +803,33 @@ impl SupportedKxGroup for KxGroup {
         &self,
         peer_pub_key: &[u8],
     ) -> Result<rustls::crypto::CompletedKeyExchange, Error> {
-        PKey::from_encoded_public_key(peer_pub_key, self.algorithm_name)
-            .and_then(|key| {
-                let mut ctx = PkeyCtx::new(&key)?;
-                ctx.encapsulate_init()?;
-                let (out, secret) = ctx.encapsulate_to_vec()?;
-                Ok(CompletedKeyExchange {
-                    group: self.named_group,
-                    pub_key: out,
-                    secret: SharedSecret::from(secret.as_slice()),
+        observe(Operation::Encapsulate, self, || {
+            self.check_pub_key_len(peer_pub_key)?;
+            PKey::from_encoded_public_key(peer_pub_key, self.algorithm_name)
+                .and_then(|key| {
+                    let mut ctx = PkeyCtx::new(&key)?;
+                    ctx.encapsulate_init()?;
+                    let (out, secret) = ctx.encapsulate_to_vec()?;
+                    Ok(CompletedKeyExchange {
+                        group: self.named_group,
+                        pub_key: out,
+                        secret: SharedSecret::from(secret.as_slice()),
+                    })
                 })
-            })
-            .map_err(|e| Error::General(format!("OpenSSL encapsulation error: {e}")))
+                .map_err(|source| {
+                    KemError::Encapsulate {
+                        algorithm: self.algorithm(),
+                        source,
+                    }
+                    .into()
+                })
+        })
     }
 }
 
 impl ActiveKeyExchange for KeyExchange {
     fn complete(self: Box<Self>, peer_pub_key: &[u8]) -> Result<SharedSecret, Error> {
-        PkeyCtx::new(&self.priv_key)
-            .and_then(|ctx| {
-                ctx.decapsulate_init()?;
-                let secret = ctx.decapsulate_to_vec(peer_pub_key)?;
-                Ok(SharedSecret::from(secret.as_slice()))
-            })
-            .map_err(|e| Error::General(format!("OpenSSL decapsulation error: {e}")))
+        self.decapsulate(peer_pub_key)
     }
 
     fn pub_key(&self) -> &[u8] {
@@ -124,40 +841,715 @@ impl ActiveKeyExchange for KeyExchange {
     }
 
     fn hybrid_component(&self) -> Option<(NamedGroup, &[u8])> {
-        if self.mlkem.named_group == NamedGroup::X25519MLKEM768 {
-            Some((
-                NamedGroup::X25519,
-                self.classical_pub_key.as_ref().unwrap().as_slice(),
-            ))
-        } else {
-            None
-        }
+        let classical_group = self.mlkem.classical_component()?;
+        Some((classical_group, self.classical_pub_key.as_ref()?.as_slice()))
     }
 
     fn complete_hybrid_component(
         self: Box<Self>,
         peer_pub_key: &[u8],
     ) -> Result<SharedSecret, Error> {
-        if self.mlkem.named_group == NamedGroup::X25519MLKEM768 {
-            PKey::public_key_from_raw_bytes(peer_pub_key, Id::X25519)
-                .and_then(|peer_pub_key| {
-                    // get the private part of the key
-                    const OQS_HYBRID_PKEY_PARAM_CLASSICAL_PRIV_KEY: &[u8] =
-                        b"hybrid_classical_priv\0";
-                    let mut private_bytes = self
-                        .priv_key
-                        .get_octet_string_param(OQS_HYBRID_PKEY_PARAM_CLASSICAL_PRIV_KEY)?;
-                    let priv_key = PKey::private_key_from_raw_bytes(&private_bytes, Id::X25519)?;
-                    private_bytes.zeroize();
-
-                    let mut deriver = Deriver::new(&priv_key)?;
-                    deriver.set_peer(&peer_pub_key)?;
-                    let secret = deriver.derive_to_vec()?;
-                    Ok(SharedSecret::from(secret.as_slice()))
-                })
-                .map_err(|e| Error::General(format!("OpenSSL error: {e}")))
+        let classical = self
+            .mlkem
+            .classical_component()
+            .and_then(ClassicalComponent::from_named_group)
+            .expect("Should only be called if hybrid_component returns Some(_)");
+
+        // get the private part of the key
+        const OQS_HYBRID_PKEY_PARAM_CLASSICAL_PRIV_KEY: &[u8] = b"hybrid_classical_priv\0";
+        self.priv_key
+            .get_octet_string_param(OQS_HYBRID_PKEY_PARAM_CLASSICAL_PRIV_KEY)
+            .map(Zeroizing::new)
+            .and_then(|private_bytes| {
+                Ok((
+                    classical.private_key(&private_bytes)?,
+                    classical.peer_public_key(peer_pub_key)?,
+                ))
+            })
+            .and_then(|(priv_key, peer_pub_key)| {
+                let mut deriver = Deriver::new(&priv_key)?;
+                deriver.set_peer(&peer_pub_key)?;
+                let secret = Zeroizing::new(deriver.derive_to_vec()?);
+                Ok(SharedSecret::from(secret.as_slice()))
+            })
+            .map_err(|source| {
+                KemError::ClassicalKeyAgreement {
+                    algorithm: self.mlkem.algorithm(),
+                    source,
+                }
+                .into()
+            })
+    }
+}
+
+/// The classical key exchange of a hybrid group.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum ClassicalComponent {
+    X25519,
+    X448,
+    P256,
+    P384,
+    P521,
+}
+
+impl ClassicalComponent {
+    /// Returns the component for a classical named group, or `None` if it isn't supported.
+    pub(crate) fn from_named_group(group: NamedGroup) -> Option<Self> {
+        match group {
+            NamedGroup::X25519 => Some(Self::X25519),
+            NamedGroup::X448 => Some(Self::X448),
+            NamedGroup::secp256r1 => Some(Self::P256),
+            NamedGroup::secp384r1 => Some(Self::P384),
+            NamedGroup::secp521r1 => Some(Self::P521),
+            _ => None,
+        }
+    }
+
+    /// Returns the length of public keys, as encoded in TLS key shares.
+    fn pub_key_len(self) -> usize {
+        match self {
+            Self::X25519 => 32,
+            Self::X448 => 56,
+            Self::P256 => 65,
+            Self::P384 => 97,
+            Self::P521 => 133,
+        }
+    }
+
+    /// Returns the length of shared secrets.
+    fn secret_len(self) -> usize {
+        match self {
+            Self::X25519 | Self::P256 => 32,
+            Self::X448 => 56,
+            Self::P384 => 48,
+            Self::P521 => 66,
+        }
+    }
+
+    /// Loads the private key, as encoded by oqsprovider's `hybrid_classical_priv` parameter.
+    fn private_key(self, bytes: &[u8]) -> Result<PKey<Private>, ErrorStack> {
+        match self {
+            Self::X25519 => PKey::private_key_from_raw_bytes(bytes, Id::X25519),
+            Self::X448 => PKey::private_key_from_raw_bytes(bytes, Id::X448),
+            // oqsprovider stores EC private keys DER encoded
+            Self::P256 | Self::P384 | Self::P521 => PKey::private_key_from_der(bytes),
+        }
+    }
+
+    /// Loads a peer's public key, as encoded in a TLS key share.
+    fn peer_public_key(self, bytes: &[u8]) -> Result<PKey<Public>, ErrorStack> {
+        match self {
+            Self::X25519 => PKey::public_key_from_raw_bytes(bytes, Id::X25519),
+            Self::X448 => PKey::public_key_from_raw_bytes(bytes, Id::X448),
+            Self::P256 => ec_public_key_from_bytes(bytes, Nid::X9_62_PRIME256V1),
+            Self::P384 => ec_public_key_from_bytes(bytes, Nid::SECP384R1),
+            Self::P521 => ec_public_key_from_bytes(bytes, Nid::SECP521R1),
+        }
+    }
+}
+
+/// Loads an encoded EC point as a public key on the specified curve.
+fn ec_public_key_from_bytes(bytes: &[u8], curve: Nid) -> Result<PKey<Public>, ErrorStack> {
+    let group = EcGroup::from_curve_name(curve)?;
+    let mut ctx = BigNumContext::new()?;
+    let point = EcPoint::from_bytes(&group, bytes, &mut ctx)?;
+    PKey::from_ec_key(EcKey::from_public_key(&group, &point)?)
+}
+
+/// Returns the algorithm name, panicking if it isn't null terminated.
+///
+/// The name is passed to OpenSSL as a C string, so reading it without the terminator would be undefined behaviour.
+const fn validate_algorithm_name(name: &'static [u8]) -> &'static [u8] {
+    assert!(
+        matches!(name.last(), Some(0)),
+        "algorithm name must be null terminated"
+    );
+    name
+}
+
+/// Returns the post-quantum security level of the named group in bits, or 0 for unknown groups.
+pub(crate) fn security_bits(group: NamedGroup) -> u32 {
+    match group {
+        NamedGroup::MLKEM512 | HQC_128 | BIKE_L1 | FRODO_640_AES => 128,
+        NamedGroup::MLKEM768
+        | NamedGroup::X25519MLKEM768
+        | NamedGroup::secp256r1MLKEM768
+        | KYBER_768
+        | X_WING => 192,
+        NamedGroup::MLKEM1024 | SECP384R1_MLKEM1024 | X448_MLKEM1024 => 256,
+        _ => 0,
+    }
+}
+
+/// Returns whether the named group uses a post-quantum key encapsulation mechanism.
+///
+/// This is true for the pure KEM and hybrid groups known to this crate, and false for classical groups such as
+/// [`NamedGroup::X25519`]. It's useful when only a [`NamedGroup`] is available, e.g to record whether a session
+/// used a post-quantum key exchange.
+pub fn named_group_is_post_quantum(group: NamedGroup) -> bool {
+    matches!(
+        group,
+        NamedGroup::MLKEM512
+            | NamedGroup::MLKEM768
+            | NamedGroup::MLKEM1024
+            | NamedGroup::X25519MLKEM768
+            | NamedGroup::secp256r1MLKEM768
+            | SECP384R1_MLKEM1024
+            | X448_MLKEM1024
+            | X_WING
+            | HQC_128
+            | BIKE_L1
+            | FRODO_640_AES
+            | KYBER_768
+    )
+}
+
+/// Returns whether the named group is a hybrid of a post-quantum KEM and a classical key exchange known to this
+/// crate, e.g [`NamedGroup::X25519MLKEM768`] or X-Wing.
+pub fn named_group_is_hybrid(group: NamedGroup) -> bool {
+    matches!(
+        group,
+        NamedGroup::X25519MLKEM768
+            | NamedGroup::secp256r1MLKEM768
+            | SECP384R1_MLKEM1024
+            | X448_MLKEM1024
+            | X_WING
+    )
+}
+
+/// Returns the classical component of a hybrid named group, and whether it follows the post-quantum component.
+fn hybrid_layout(group: NamedGroup) -> Option<(ClassicalComponent, bool)> {
+    match group {
+        NamedGroup::X25519MLKEM768 | X_WING => Some((ClassicalComponent::X25519, true)),
+        NamedGroup::secp256r1MLKEM768 => Some((ClassicalComponent::P256, false)),
+        SECP384R1_MLKEM1024 => Some((ClassicalComponent::P384, false)),
+        X448_MLKEM1024 => Some((ClassicalComponent::X448, false)),
+        _ => None,
+    }
+}
+
+/// Splits a hybrid public key, as sent in a TLS key share, into its classical and post-quantum components.
+///
+/// Most hybrid groups put the classical component first, but [`NamedGroup::X25519MLKEM768`] and X-Wing put
+/// the post-quantum component first. Returns `None` for groups that aren't hybrids, see [`named_group_is_hybrid`],
+/// or if the public key is shorter than the classical component.
+pub fn split_hybrid_pub_key(pub_key: &[u8], group: NamedGroup) -> Option<(&[u8], &[u8])> {
+    let (classical, pq_first) = hybrid_layout(group)?;
+    split_hybrid(pub_key, classical.pub_key_len(), pq_first)
+}
+
+/// Splits a hybrid shared secret into its classical and post-quantum components, e.g to inspect them when
+/// debugging.
+///
+/// The components are concatenated in the same order as in public keys, see [`split_hybrid_pub_key`]. Returns
+/// `None` for groups that aren't hybrids or that hash the components together, like X-Wing, or if the secret is
+/// shorter than the classical component.
+pub fn split_hybrid_secret(secret: &SharedSecret, group: NamedGroup) -> Option<(&[u8], &[u8])> {
+    if group == X_WING {
+        return None;
+    }
+    let (classical, pq_first) = hybrid_layout(group)?;
+    split_hybrid(secret.secret_bytes(), classical.secret_len(), pq_first)
+}
+
+/// Splits `bytes` into the classical component of `classical_len` bytes and the post-quantum component.
+fn split_hybrid(bytes: &[u8], classical_len: usize, pq_first: bool) -> Option<(&[u8], &[u8])> {
+    if pq_first {
+        let pq_len = bytes.len().checked_sub(classical_len)?;
+        let (pq, classical) = bytes.split_at(pq_len);
+        Some((classical, pq))
+    } else {
+        bytes.split_at_checked(classical_len)
+    }
+}
+
+/// Combines the classical and post-quantum components of a hybrid public key, the inverse of
+/// [`split_hybrid_pub_key`].
+///
+/// Returns `None` for groups that aren't hybrids.
+pub fn combine_hybrid_pub_key(classical: &[u8], pq: &[u8], group: NamedGroup) -> Option<Vec<u8>> {
+    let (_, pq_first) = hybrid_layout(group)?;
+    Some(if pq_first {
+        [pq, classical].concat()
+    } else {
+        [classical, pq].concat()
+    })
+}
+
+/// Returns whether the named group only uses algorithms approved for FIPS 140-3.
+///
+/// ML-KEM is approved by [FIPS 203], and so are hybrids of ML-KEM with the NIST curves P-256, P-384 and P-521.
+/// X25519 and X448 aren't approved, so neither are their hybrids or X-Wing. Kyber, HQC, BIKE and FrodoKEM aren't
+/// NIST standards.
+///
+/// This only reports whether the algorithms are approved; it doesn't mean that OpenSSL or oqsprovider are running
+/// in a FIPS validated configuration.
+///
+/// [FIPS 203]: https://csrc.nist.gov/pubs/fips/203/final
+pub fn is_fips_approved(group: NamedGroup) -> bool {
+    matches!(
+        group,
+        NamedGroup::MLKEM512
+            | NamedGroup::MLKEM768
+            | NamedGroup::MLKEM1024
+            | NamedGroup::secp256r1MLKEM768
+            | SECP384R1_MLKEM1024
+    )
+}
+
+/// Extension methods for [`ActiveKeyExchange`].
+pub trait ActiveKeyExchangeExt {
+    /// Completes the key exchange, then verifies the shared secret against a MAC sent by the peer.
+    ///
+    /// `expected_secret_mac` must be the HMAC-SHA256 of `peer_pub_key`, keyed with the shared secret.
+    /// The comparison is constant time, and on mismatch the secret is discarded and
+    /// [`KemError::SecretMacMismatch`] returned.
+    fn complete_and_verify(
+        self: Box<Self>,
+        peer_pub_key: &[u8],
+        expected_secret_mac: &[u8],
+    ) -> Result<SharedSecret, Error>;
+
+    /// Returns a conservative estimate of how long [`ActiveKeyExchange::complete`] will take.
+    ///
+    /// This can be used to choose a read timeout before completing the key exchange. The estimates are static
+    /// approximations for the group and whether the CPU supports AVX2, not measurements. On CPUs other than
+    /// x86-64 the acceleration isn't known, so this returns a conservative upper bound of 50 ms.
+    fn estimated_completion_time(&self) -> Duration;
+}
+
+impl ActiveKeyExchangeExt for dyn ActiveKeyExchange {
+    fn complete_and_verify(
+        self: Box<Self>,
+        peer_pub_key: &[u8],
+        expected_secret_mac: &[u8],
+    ) -> Result<SharedSecret, Error> {
+        let secret = self.complete(peer_pub_key)?;
+        let mac = secret_mac(&secret, peer_pub_key)?;
+        if bool::from(mac.ct_eq(expected_secret_mac)) {
+            Ok(secret)
         } else {
-            unreachable!("Should only be called if hybrid_component returns Some(_)")
+            Err(KemError::SecretMacMismatch.into())
+        }
+    }
+
+    fn estimated_completion_time(&self) -> Duration {
+        estimated_completion_time(self.group(), has_avx2())
+    }
+}
+
+/// Returns whether the CPU supports AVX2, or `None` if the acceleration of other CPUs isn't known.
+fn has_avx2() -> Option<bool> {
+    #[cfg(target_arch = "x86_64")]
+    {
+        Some(std::arch::is_x86_feature_detected!("avx2"))
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    {
+        None
+    }
+}
+
+/// The estimate when the group or the CPU's acceleration isn't known, which may be much slower, e.g. an
+/// embedded CPU.
+const CONSERVATIVE_COMPLETION_TIME: Duration = Duration::from_millis(50);
+
+fn estimated_completion_time(group: NamedGroup, accelerated: Option<bool>) -> Duration {
+    let Some(accelerated) = accelerated else {
+        return CONSERVATIVE_COMPLETION_TIME;
+    };
+    let (accelerated_micros, portable_micros) = match group {
+        NamedGroup::MLKEM512 => (100, 300),
+        NamedGroup::MLKEM768 | KYBER_768 => (200, 500),
+        NamedGroup::MLKEM1024 => (300, 800),
+        NamedGroup::X25519MLKEM768 => (300, 700),
+        NamedGroup::secp256r1MLKEM768 => (400, 900),
+        SECP384R1_MLKEM1024 => (1_000, 2_000),
+        X448_MLKEM1024 => (600, 1_500),
+        X_WING => (300, 700),
+        FRODO_640_AES => (1_000, 2_000),
+        BIKE_L1 => (1_000, 3_000),
+        HQC_128 => (300, 1_000),
+        _ => return CONSERVATIVE_COMPLETION_TIME,
+    };
+    Duration::from_micros(if accelerated {
+        accelerated_micros
+    } else {
+        portable_micros
+    })
+}
+
+/// Computes the HMAC-SHA256 of `message`, keyed with the shared secret.
+pub(crate) fn secret_mac(secret: &SharedSecret, message: &[u8]) -> Result<Vec<u8>, Error> {
+    PKey::hmac(secret.secret_bytes())
+        .and_then(|key| {
+            let mut signer = Signer::new(MessageDigest::sha256(), &key)?;
+            signer.update(message)?;
+            signer.sign_to_vec()
+        })
+        .map_err(|source| KemError::SecretMac { source }.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+    use rustls::crypto::SupportedKxGroup;
+    use rustls::NamedGroup;
+
+    use super::{ClassicalComponent, KeyExchange, KxGroup};
+    use crate::ffi::PkeyCtxExt;
+    use crate::tests::load_providers;
+    use openssl::pkey_ctx::PkeyCtx;
+    use rustls::crypto::ActiveKeyExchange;
+
+    #[test]
+    fn repeated_encapsulation() {
+        load_providers();
+
+        for group in [KxGroup::MLKEM768, KxGroup::X25519MLKEM768] {
+            let kx = KeyExchange::generate(group).unwrap();
+
+            let mut ciphertexts: Vec<Vec<u8>> = Vec::new();
+            for _ in 0..10 {
+                let completed = group.start_and_complete(&kx.pub_key).unwrap();
+                assert_eq!(
+                    Some(completed.secret.secret_bytes().len()),
+                    group.shared_secret_size()
+                );
+                assert!(!ciphertexts.contains(&completed.pub_key), "{group}");
+
+                let secret = kx.decapsulate(&completed.pub_key).unwrap();
+                assert_eq!(secret.secret_bytes(), completed.secret.secret_bytes());
+                ciphertexts.push(completed.pub_key);
+            }
         }
     }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        /// Decapsulating a ciphertext with a corrupted byte never recovers the encapsulated secret.
+        ///
+        /// Only the low 7 bits are flipped, as X25519 ignores the top bit of public keys.
+        #[test]
+        fn corrupted_ciphertext(
+            group in prop::sample::select(vec![KxGroup::MLKEM768, KxGroup::X25519MLKEM768]),
+            index in any::<prop::sample::Index>(),
+            mask in 1u8..0x80,
+        ) {
+            load_providers();
+
+            let kx = KeyExchange::generate(group).unwrap();
+            let completed = group.start_and_complete(&kx.pub_key).unwrap();
+            let mut ciphertext = completed.pub_key;
+            let index = index.index(ciphertext.len());
+            ciphertext[index] ^= mask;
+
+            if let Ok(secret) = kx.decapsulate(&ciphertext) {
+                prop_assert_ne!(secret.secret_bytes(), completed.secret.secret_bytes());
+            }
+        }
+
+        /// Truncated ciphertexts are rejected before decapsulation.
+        #[test]
+        fn truncated_ciphertext(
+            group in prop::sample::select(vec![KxGroup::MLKEM768, KxGroup::X25519MLKEM768]),
+            index in any::<prop::sample::Index>(),
+        ) {
+            load_providers();
+
+            let kx = KeyExchange::generate(group).unwrap();
+            let completed = group.start_and_complete(&kx.pub_key).unwrap();
+            let len = index.index(completed.pub_key.len());
+            prop_assert!(kx.decapsulate(&completed.pub_key[..len]).is_err());
+        }
+    }
+
+    #[test]
+    fn estimated_completion_time() {
+        use super::estimated_completion_time;
+        use std::time::Duration;
+
+        for accelerated in [Some(true), Some(false)] {
+            let mlkem768 = estimated_completion_time(NamedGroup::MLKEM768, accelerated);
+            assert!(mlkem768 < estimated_completion_time(NamedGroup::MLKEM1024, accelerated));
+            assert!(mlkem768 < estimated_completion_time(NamedGroup::X25519MLKEM768, accelerated));
+            assert_eq!(
+                estimated_completion_time(NamedGroup::Unknown(0xfe00), accelerated),
+                Duration::from_millis(50)
+            );
+        }
+        assert!(
+            estimated_completion_time(NamedGroup::MLKEM768, Some(true))
+                < estimated_completion_time(NamedGroup::MLKEM768, Some(false))
+        );
+        assert_eq!(
+            estimated_completion_time(NamedGroup::MLKEM768, None),
+            Duration::from_millis(50)
+        );
+    }
+
+    /// Checks the buffers are zeroized by `zeroize_buffers`, which is what `Drop` calls. The buffers are freed
+    /// after `Drop` runs, so can't be checked through a real drop.
+    #[test]
+    fn zeroize_buffers() {
+        load_providers();
+
+        let mut kx = KeyExchange::generate(KxGroup::X25519MLKEM768).unwrap();
+        let pub_key = (kx.pub_key.as_ptr(), kx.pub_key.len());
+        let classical_pub_key = kx
+            .classical_pub_key
+            .as_ref()
+            .map(|key| (key.as_ptr(), key.len()))
+            .unwrap();
+
+        // Zeroize without freeing the buffers, so they can still be read
+        kx.zeroize_buffers();
+        for (ptr, len) in [pub_key, classical_pub_key] {
+            let bytes = unsafe { std::slice::from_raw_parts(ptr, len) };
+            assert!(bytes.iter().all(|b| *b == 0));
+        }
+    }
+
+    #[test]
+    fn raw_parts_roundtrip() {
+        load_providers();
+
+        for group in [KxGroup::MLKEM768, KxGroup::X25519MLKEM768] {
+            let kx = group.start_key_exchange().unwrap();
+            let completed = group.start_and_complete(kx.pub_key()).unwrap();
+
+            let (pub_key, private_key_der, raw_group) = kx.into_raw_parts().unwrap();
+            assert_eq!(raw_group.named_group, group.named_group);
+
+            let kx =
+                KeyExchange::from_raw_parts(pub_key, private_key_der.to_vec(), raw_group).unwrap();
+            let secret = kx.complete(&completed.pub_key).unwrap();
+            assert_eq!(secret.secret_bytes(), completed.secret.secret_bytes());
+        }
+    }
+
+    #[test]
+    fn start_with_seed() {
+        load_providers();
+
+        let group = KxGroup::MLKEM768;
+        let kx = group.start_with_seed(&[1; 32]).unwrap();
+        assert_eq!(
+            kx.pub_key(),
+            group.start_with_seed(&[1; 32]).unwrap().pub_key()
+        );
+        assert_ne!(
+            kx.pub_key(),
+            group.start_with_seed(&[2; 32]).unwrap().pub_key()
+        );
+
+        let completed = group.start_and_complete(kx.pub_key()).unwrap();
+        let secret = kx.complete(&completed.pub_key).unwrap();
+        assert_eq!(secret.secret_bytes(), completed.secret.secret_bytes());
+    }
+
+    #[test]
+    fn from_pkey() {
+        load_providers();
+
+        for group in [KxGroup::MLKEM768, KxGroup::X25519MLKEM768] {
+            let mut ctx = PkeyCtx::<()>::new_from_name(group.algorithm_name).unwrap();
+            ctx.keygen_init().unwrap();
+            let kx = KeyExchange::from_pkey(ctx.keygen().unwrap(), group).unwrap();
+
+            let completed = group.start_and_complete(kx.pub_key()).unwrap();
+            let secret = kx.complete(&completed.pub_key).unwrap();
+            assert_eq!(secret.secret_bytes(), completed.secret.secret_bytes());
+        }
+    }
+
+    #[test]
+    fn debug_omits_key_material() {
+        load_providers();
+
+        let kx = KeyExchange::generate(KxGroup::MLKEM768).unwrap();
+        let debug = format!("{kx:?}");
+        assert_eq!(
+            debug,
+            format!(
+                "KeyExchange {{ group: MLKEM768, pub_key_len: {} }}",
+                kx.pub_key.len()
+            )
+        );
+        assert!(!debug.to_lowercase().contains("private"));
+
+        let longest_hex_run = debug
+            .split(|c: char| !c.is_ascii_hexdigit())
+            .map(str::len)
+            .max()
+            .unwrap_or(0);
+        assert!(longest_hex_run <= 32, "{debug}");
+    }
+
+    #[test]
+    #[should_panic(expected = "algorithm name must be null terminated")]
+    fn algorithm_name_without_null_terminator() {
+        KxGroup::new(NamedGroup::MLKEM768, b"mlkem768");
+    }
+
+    #[test]
+    #[should_panic(expected = "algorithm name must be null terminated")]
+    fn empty_custom_algorithm_name() {
+        KxGroup::MLKEM768.with_custom_algorithm_name(b"");
+    }
+
+    #[test]
+    fn classical_component_keys() {
+        use openssl::derive::Deriver;
+        use openssl::ec::{EcGroup, EcKey, PointConversionForm};
+        use openssl::nid::Nid;
+        use openssl::pkey::PKey;
+
+        // Keys are encoded as oqsprovider does: raw bytes for X25519 and X448, and DER encoded private keys
+        // with uncompressed points for the NIST curves
+        let mut keys = vec![];
+        for (group, private_key) in [
+            (NamedGroup::X25519, PKey::generate_x25519().unwrap()),
+            (NamedGroup::X448, PKey::generate_x448().unwrap()),
+        ] {
+            let private_bytes = private_key.raw_private_key().unwrap();
+            let public_bytes = private_key.raw_public_key().unwrap();
+            keys.push((group, private_bytes, public_bytes));
+        }
+        for (group, curve) in [
+            (NamedGroup::secp256r1, Nid::X9_62_PRIME256V1),
+            (NamedGroup::secp384r1, Nid::SECP384R1),
+            (NamedGroup::secp521r1, Nid::SECP521R1),
+        ] {
+            let curve = EcGroup::from_curve_name(curve).unwrap();
+            let ec_key = EcKey::generate(&curve).unwrap();
+            let mut ctx = openssl::bn::BigNumContext::new().unwrap();
+            let public_bytes = ec_key
+                .public_key()
+                .to_bytes(&curve, PointConversionForm::UNCOMPRESSED, &mut ctx)
+                .unwrap();
+            keys.push((group, ec_key.private_key_to_der().unwrap(), public_bytes));
+        }
+
+        for (group, private_bytes, public_bytes) in keys {
+            let component = ClassicalComponent::from_named_group(group).unwrap();
+            let private_key = component.private_key(&private_bytes).unwrap();
+            let public_key = component.peer_public_key(&public_bytes).unwrap();
+            let mut deriver = Deriver::new(&private_key).unwrap();
+            deriver.set_peer(&public_key).unwrap();
+            assert!(!deriver.derive_to_vec().unwrap().is_empty(), "{group:?}");
+            assert!(component.peer_public_key(&public_bytes[1..]).is_err());
+        }
+        assert_eq!(
+            ClassicalComponent::from_named_group(NamedGroup::MLKEM768),
+            None
+        );
+    }
+
+    #[test]
+    fn split_hybrid_pub_key() {
+        use super::{combine_hybrid_pub_key, split_hybrid_pub_key};
+
+        for (group, classical_len, pq_len, pq_first) in [
+            (NamedGroup::X25519MLKEM768, 32, 1184, true),
+            (NamedGroup::secp256r1MLKEM768, 65, 1184, false),
+            (super::SECP384R1_MLKEM1024, 97, 1568, false),
+            (super::X448_MLKEM1024, 56, 1568, false),
+            (crate::xwing::X_WING, 32, 1184, true),
+        ] {
+            let classical = vec![1; classical_len];
+            let pq = vec![2; pq_len];
+            let pub_key = combine_hybrid_pub_key(&classical, &pq, group).unwrap();
+            assert_eq!(pub_key[0], if pq_first { 2 } else { 1 }, "{group:?}");
+            assert_eq!(
+                split_hybrid_pub_key(&pub_key, group),
+                Some((classical.as_slice(), pq.as_slice()))
+            );
+            assert_eq!(split_hybrid_pub_key(&classical[1..], group), None);
+        }
+
+        assert_eq!(split_hybrid_pub_key(&[0; 1184], NamedGroup::MLKEM768), None);
+        assert_eq!(
+            combine_hybrid_pub_key(&[], &[0; 1184], NamedGroup::MLKEM768),
+            None
+        );
+    }
+
+    #[test]
+    fn split_hybrid_pub_key_matches_encoding() {
+        load_providers();
+        let kx = KxGroup::X25519MLKEM768.start().unwrap();
+        let (group, classical_pub_key) = kx.hybrid_component().unwrap();
+        assert_eq!(group, NamedGroup::X25519);
+
+        let (classical, pq) =
+            super::split_hybrid_pub_key(kx.pub_key(), NamedGroup::X25519MLKEM768).unwrap();
+        assert_eq!(classical, classical_pub_key);
+        assert_eq!(pq.len(), 1184);
+    }
+
+    #[test]
+    fn split_hybrid_secret() {
+        use super::{combine_hybrid_pub_key, split_hybrid_pub_key, split_hybrid_secret};
+        use openssl::derive::Deriver;
+        use openssl::ec::{EcGroup, EcKey, PointConversionForm};
+        use openssl::nid::Nid;
+        use openssl::pkey::PKey;
+        use rustls::crypto::SharedSecret;
+
+        load_providers();
+
+        // The client is built from standalone component keys, so each half of the hybrid secret can be checked
+        // against a standalone key agreement. These groups cover both component orders.
+        for (group, component) in [
+            (NamedGroup::X25519MLKEM768, ClassicalComponent::X25519),
+            (NamedGroup::secp256r1MLKEM768, ClassicalComponent::P256),
+        ] {
+            let (classical_key, classical_pub_key) = match component {
+                ClassicalComponent::X25519 => {
+                    let key = PKey::generate_x25519().unwrap();
+                    let pub_key = key.raw_public_key().unwrap();
+                    (key, pub_key)
+                }
+                _ => {
+                    let curve = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap();
+                    let ec_key = EcKey::generate(&curve).unwrap();
+                    let mut ctx = openssl::bn::BigNumContext::new().unwrap();
+                    let pub_key = ec_key
+                        .public_key()
+                        .to_bytes(&curve, PointConversionForm::UNCOMPRESSED, &mut ctx)
+                        .unwrap();
+                    (PKey::from_ec_key(ec_key).unwrap(), pub_key)
+                }
+            };
+            let mlkem = KeyExchange::generate(KxGroup::MLKEM768).unwrap();
+            let client_pub_key =
+                combine_hybrid_pub_key(&classical_pub_key, &mlkem.pub_key, group).unwrap();
+
+            let server = KxGroup::from_named_group(group)
+                .unwrap()
+                .start_and_complete(&client_pub_key)
+                .unwrap();
+            let (server_classical, ciphertext) =
+                split_hybrid_pub_key(&server.pub_key, group).unwrap();
+
+            let mlkem_secret = Box::new(mlkem).complete(ciphertext).unwrap();
+            let server_classical = component.peer_public_key(server_classical).unwrap();
+            let mut deriver = Deriver::new(&classical_key).unwrap();
+            deriver.set_peer(&server_classical).unwrap();
+            let classical_secret = deriver.derive_to_vec().unwrap();
+
+            assert_eq!(
+                split_hybrid_secret(&server.secret, group),
+                Some((classical_secret.as_slice(), mlkem_secret.secret_bytes())),
+                "{group:?}"
+            );
+        }
+
+        let secret = SharedSecret::from(&[0; 32][..]);
+        assert_eq!(split_hybrid_secret(&secret, crate::xwing::X_WING), None);
+        assert_eq!(split_hybrid_secret(&secret, NamedGroup::MLKEM768), None);
+        assert_eq!(split_hybrid_secret(&secret, super::X448_MLKEM1024), None);
+    }
 }
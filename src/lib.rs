@@ -1,66 +1,292 @@
 //! Experimental post-quantum key exchange algorithms for rustls using OpenSSL and liboqs.
 #![deny(missing_docs)]
-mod ffi;
+mod config;
+mod error;
+mod fallback;
+pub mod ffi;
+mod hybrid;
 mod kem;
+mod metrics;
+mod observe;
+mod parse;
+mod pool;
+mod provider;
+mod pubkey;
+mod set;
+mod swap;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod test_utils;
+mod validate;
+mod version;
+mod xwing;
 
+#[cfg(all(feature = "rustls-openssl", feature = "webpki-roots"))]
+pub use config::make_client_config_with_webpki_roots;
+pub use config::make_pq_kx_groups;
+#[cfg(feature = "rustls-openssl")]
+pub use config::{install_default_provider, make_client_config};
+pub use error::KemError;
+pub use fallback::FallbackKxGroup;
+
+pub use hybrid::{ConcatCombiner, HkdfExtractCombiner, HybridKxGroup, SecretCombiner};
+
+pub use kem::is_fips_approved;
+pub use kem::ActiveKeyExchangeExt;
+pub use kem::KeyAgreementMode;
+pub use kem::KeyExchange;
+pub use kem::KxGroup;
+pub use kem::KxGroupMetadata;
+pub use kem::BIKEL1;
+pub use kem::FRODO640AES;
+pub use kem::HQC128;
+#[allow(deprecated)]
+pub use kem::KYBER768;
+pub use kem::MLKEM1024;
+pub use kem::MLKEM512;
 pub use kem::MLKEM768;
+pub use kem::P256MLKEM768;
+pub use kem::P384MLKEM1024;
 pub use kem::X25519MLKEM768;
+pub use kem::X448MLKEM1024;
+pub use kem::{combine_hybrid_pub_key, split_hybrid_pub_key, split_hybrid_secret};
+pub use kem::{named_group_is_hybrid, named_group_is_post_quantum};
+pub use metrics::{metrics, KemMetrics};
+pub use parse::ParseError;
+pub use pool::KxGroupPool;
+pub use provider::{
+    check_oqs_provider_available, load_oqs_provider, oqs_provider_version, OqsProviderGuard,
+};
+pub use pubkey::{DerError, PemError, PubKey};
+pub use set::KxGroupSet;
+pub use swap::ReversingKeyExchange;
+pub use validate::{ConfigWarning, PqConfigValidator};
+pub use version::{OqsProviderVersion, ParseVersionError};
+pub use xwing::{XWingKxGroup, XWING};
+
+use rustls::crypto::SupportedKxGroup;
+use rustls::NamedGroup;
+
+/// All key exchange groups provided by this crate, in order of preference.
+///
+/// Hybrid groups are preferred over pure post-quantum groups, and stronger groups over weaker ones.
+/// The deprecated [`KYBER768`] group isn't included.
+pub static ALL_KX_GROUPS: &[&dyn SupportedKxGroup] = &[
+    P384MLKEM1024,
+    X448MLKEM1024,
+    XWING,
+    X25519MLKEM768,
+    P256MLKEM768,
+    MLKEM1024,
+    MLKEM768,
+    MLKEM512,
+    HQC128,
+    BIKEL1,
+    FRODO640AES,
+];
+
+/// The number of hybrid groups at the start of [`ALL_KX_GROUPS`].
+const HYBRID_KX_GROUPS_LEN: usize = 5;
+
+/// Returns all key exchange groups provided by this crate, in order of preference.
+///
+/// See [`ALL_KX_GROUPS`].
+pub fn all_kx_groups() -> &'static [&'static dyn SupportedKxGroup] {
+    ALL_KX_GROUPS
+}
+
+/// Returns the hybrid key exchange groups provided by this crate, in order of preference.
+///
+/// These combine a post-quantum KEM with a classical key exchange, e.g [`X25519MLKEM768`].
+pub fn hybrid_kx_groups() -> &'static [&'static dyn SupportedKxGroup] {
+    &ALL_KX_GROUPS[..HYBRID_KX_GROUPS_LEN]
+}
+
+/// Returns the pure post-quantum key exchange groups provided by this crate, in order of preference.
+pub fn pure_pq_kx_groups() -> &'static [&'static dyn SupportedKxGroup] {
+    &ALL_KX_GROUPS[HYBRID_KX_GROUPS_LEN..]
+}
+
+/// Returns the key exchange group provided by this crate for the named group, if any.
+pub fn kx_group_by_named_group(group: NamedGroup) -> Option<&'static dyn SupportedKxGroup> {
+    #[allow(deprecated)]
+    ALL_KX_GROUPS
+        .iter()
+        .chain([&KYBER768])
+        .find(|kx_group| kx_group.name() == group)
+        .copied()
+}
+
+/// Returns the key exchange groups from [`all_kx_groups`] with at least `min_bits` of post-quantum security.
+///
+/// See [`KxGroup::security_bits`]. The groups are returned in order of preference.
+pub fn groups_at_security_level(min_bits: u32) -> Vec<&'static dyn SupportedKxGroup> {
+    all_kx_groups()
+        .iter()
+        .filter(|group| kem::security_bits(group.name()) >= min_bits)
+        .copied()
+        .collect()
+}
+
+/// Returns the key exchange groups from [`all_kx_groups`] that only use FIPS 140-3 approved algorithms.
+///
+/// See [`is_fips_approved`]. The groups are returned in order of preference.
+pub fn fips_approved_groups() -> Vec<&'static dyn SupportedKxGroup> {
+    all_kx_groups()
+        .iter()
+        .filter(|group| is_fips_approved(group.name()))
+        .copied()
+        .collect()
+}
 
 #[cfg(test)]
 mod tests {
     use once_cell::sync::OnceCell;
-    use openssl::provider::Provider;
     use rustls::crypto::SupportedKxGroup;
 
-    use crate::{MLKEM768, X25519MLKEM768};
+    use crate::kem::secret_mac;
+    use crate::provider::OqsProviderGuard;
+    use crate::{
+        ActiveKeyExchangeExt, KeyAgreementMode, KxGroup, BIKEL1, FRODO640AES, HQC128, MLKEM1024,
+        MLKEM512, MLKEM768, P256MLKEM768, P384MLKEM1024, X25519MLKEM768, X448MLKEM1024, XWING,
+    };
 
-    fn load_providers() {
-        static INSTANCE: OnceCell<Vec<Provider>> = OnceCell::new();
-        INSTANCE.get_or_init(|| {
-            let default_provider = openssl::provider::Provider::load(None, "default").unwrap();
-            let oqs_provider = openssl::provider::Provider::load(None, "oqsprovider").unwrap();
-            let _ = openssl::error::ErrorStack::get();
-            vec![default_provider, oqs_provider]
-        });
+    pub(crate) fn load_providers() {
+        static INSTANCE: OnceCell<OqsProviderGuard> = OnceCell::new();
+        INSTANCE.get_or_init(|| OqsProviderGuard::load().unwrap());
     }
 
+    /// Runs [`crate::test_utils::roundtrip`], and checks the shared secret has the size of the crate's group.
+    ///
+    /// The size isn't checked for other groups, e.g a [`HybridKxGroup`](crate::HybridKxGroup) with a custom
+    /// codepoint.
     pub(crate) fn roundtrip(ours: &dyn SupportedKxGroup, theirs: &dyn SupportedKxGroup) {
         load_providers();
+        crate::test_utils::roundtrip(ours, theirs);
 
-        let our_kx = ours.start().unwrap();
-        let their_kx = theirs.start().unwrap();
-
-        let our_completed = ours.start_and_complete(their_kx.pub_key()).unwrap();
-        let their_secret = their_kx.complete(&our_completed.pub_key).unwrap();
-
+        let expected_len = if ours.name() == XWING.name() {
+            Some(32)
+        } else {
+            KxGroup::from_named_group(ours.name()).and_then(|group| group.shared_secret_size())
+        };
+        let Some(expected_len) = expected_len else {
+            return;
+        };
+        let completed = ours
+            .start_and_complete(ours.start().unwrap().pub_key())
+            .unwrap();
         assert_eq!(
-            our_completed.secret.secret_bytes(),
-            their_secret.secret_bytes()
+            completed.secret.secret_bytes().len(),
+            expected_len,
+            "{:?}",
+            ours.name()
         );
+    }
 
-        let their_completed = theirs.start_and_complete(our_kx.pub_key()).unwrap();
-        let our_secret = our_kx.complete(&their_completed.pub_key).unwrap();
+    pub(crate) fn roundtrip_classical(ours: &dyn SupportedKxGroup, theirs: &dyn SupportedKxGroup) {
+        load_providers();
+        crate::test_utils::roundtrip_classical(ours, theirs);
+    }
 
-        assert_eq!(
-            our_secret.secret_bytes(),
-            their_completed.secret.secret_bytes()
-        );
+    #[test]
+    fn complete_and_verify() {
+        load_providers();
+
+        for group in [MLKEM768, X25519MLKEM768] {
+            let our_kx = group.start().unwrap();
+            let their_completed = group.start_and_complete(our_kx.pub_key()).unwrap();
+            let mac = secret_mac(&their_completed.secret, &their_completed.pub_key).unwrap();
+
+            let our_secret = our_kx
+                .complete_and_verify(&their_completed.pub_key, &mac)
+                .unwrap();
+            assert_eq!(
+                our_secret.secret_bytes(),
+                their_completed.secret.secret_bytes()
+            );
+        }
     }
 
-    pub(crate) fn roundtrip_classical(ours: &dyn SupportedKxGroup, theirs: &dyn SupportedKxGroup) {
+    #[test]
+    fn complete_and_verify_bad_mac() {
         load_providers();
 
-        let our_kx = ours.start().unwrap();
-        let their_kx = theirs.start().unwrap();
+        for group in [MLKEM768, X25519MLKEM768] {
+            let our_kx = group.start().unwrap();
+            let their_completed = group.start_and_complete(our_kx.pub_key()).unwrap();
+            let mut mac = secret_mac(&their_completed.secret, &their_completed.pub_key).unwrap();
+            mac[0] ^= 1;
 
-        let (_group, our_key) = our_kx.hybrid_component().unwrap();
-        let our_key = our_key.to_vec();
+            let err = our_kx
+                .complete_and_verify(&their_completed.pub_key, &mac)
+                .err()
+                .unwrap();
+            let rustls::Error::Other(rustls::OtherError(err)) = err else {
+                panic!("unexpected error: {err:?}");
+            };
+            assert!(matches!(
+                err.downcast_ref::<crate::KemError>(),
+                Some(crate::KemError::SecretMacMismatch)
+            ));
+        }
+    }
+
+    #[test]
+    fn mlkem512() {
+        roundtrip(MLKEM512, MLKEM512);
+    }
+
+    #[test]
+    fn hqc128() {
+        roundtrip(HQC128, HQC128);
+    }
+
+    #[test]
+    fn hqc128_wrong_ciphertext_length() {
+        load_providers();
+
+        let kx = HQC128.start().unwrap();
+        let completed = HQC128.start_and_complete(kx.pub_key()).unwrap();
+        let truncated = &completed.pub_key[..completed.pub_key.len() - 1];
+        assert!(kx.complete(truncated).is_err());
+    }
 
-        let peer_pub_key = their_kx.pub_key();
-        let our_secret = our_kx.complete_hybrid_component(peer_pub_key).unwrap();
-        let their_secret = their_kx.complete(&our_key).unwrap();
+    #[test]
+    fn bikel1() {
+        load_providers();
 
-        assert_eq!(our_secret.secret_bytes(), their_secret.secret_bytes());
+        // BIKE-L1 public keys are 1541 bytes, much larger than ML-KEM-512's
+        let kx = BIKEL1.start().unwrap();
+        assert!((1_500..=1_600).contains(&kx.pub_key().len()));
+
+        roundtrip(BIKEL1, BIKEL1);
+    }
+
+    #[test]
+    fn frodo640aes() {
+        load_providers();
+
+        // Key sizes from the FrodoKEM NIST submission
+        let kx = FRODO640AES.start().unwrap();
+        assert_eq!(kx.pub_key().len(), 9616);
+        let completed = FRODO640AES.start_and_complete(kx.pub_key()).unwrap();
+        assert_eq!(completed.pub_key.len(), 9720);
+
+        roundtrip(FRODO640AES, FRODO640AES);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn kyber768() {
+        use crate::KYBER768;
+
+        roundtrip(KYBER768, KYBER768);
+
+        // Kyber768 and ML-KEM-768 have the same key sizes, but are distinct algorithms so mustn't agree on a
+        // secret
+        let kx = KYBER768.start().unwrap();
+        let completed = MLKEM768.start_and_complete(kx.pub_key()).unwrap();
+        let secret = kx.complete(&completed.pub_key).unwrap();
+        assert_ne!(secret.secret_bytes(), completed.secret.secret_bytes());
     }
 
     #[test]
@@ -68,13 +294,653 @@ mod tests {
         roundtrip(MLKEM768, rustls_post_quantum::MLKEM768);
     }
 
+    #[test]
+    fn mlkem1024() {
+        roundtrip(MLKEM1024, rustls::crypto::aws_lc_rs::kx_group::MLKEM1024);
+    }
+
     #[test]
     fn x25519_mlkem768() {
         roundtrip(X25519MLKEM768, rustls_post_quantum::X25519MLKEM768);
     }
 
+    #[test]
+    fn custom_algorithm_name() {
+        let group = KxGroup::MLKEM768.with_custom_algorithm_name(b"MLKEM768\0");
+        assert_eq!(group.name(), rustls::NamedGroup::MLKEM768);
+        roundtrip(&group, rustls_post_quantum::MLKEM768);
+    }
+
+    #[test]
+    fn post_quantum_component_group() {
+        assert_eq!(
+            KxGroup::MLKEM768.post_quantum_component_group(),
+            rustls::NamedGroup::MLKEM768
+        );
+        assert_eq!(
+            KxGroup::X25519MLKEM768.post_quantum_component_group(),
+            rustls::NamedGroup::MLKEM768
+        );
+    }
+
+    #[test]
+    fn key_agreement_mode() {
+        assert_eq!(
+            KxGroup::MLKEM768.key_agreement_mode(),
+            KeyAgreementMode::Kem
+        );
+        assert_eq!(
+            KxGroup::X25519MLKEM768.key_agreement_mode(),
+            KeyAgreementMode::HybridKemEcdh
+        );
+    }
+
+    #[test]
+    fn is_hybrid() {
+        assert!(!KxGroup::MLKEM768.is_hybrid());
+        assert_eq!(KxGroup::MLKEM768.classical_component(), None);
+        assert!(!KxGroup::HQC128.is_hybrid());
+
+        for (group, classical) in [
+            (KxGroup::X25519MLKEM768, rustls::NamedGroup::X25519),
+            (KxGroup::P256MLKEM768, rustls::NamedGroup::secp256r1),
+            (KxGroup::P384MLKEM1024, rustls::NamedGroup::secp384r1),
+            (KxGroup::X448MLKEM1024, rustls::NamedGroup::X448),
+        ] {
+            assert!(group.is_hybrid());
+            assert_eq!(group.classical_component(), Some(classical));
+        }
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn security_bits() {
+        for (group, bits, classical_bits) in [
+            (KxGroup::MLKEM512, 128, None),
+            (KxGroup::MLKEM768, 192, None),
+            (KxGroup::MLKEM1024, 256, None),
+            (KxGroup::HQC128, 128, None),
+            (KxGroup::BIKEL1, 128, None),
+            (KxGroup::FRODO640AES, 128, None),
+            (KxGroup::KYBER768, 192, None),
+            (KxGroup::X25519MLKEM768, 192, Some(128)),
+            (KxGroup::P256MLKEM768, 192, Some(128)),
+            (KxGroup::P384MLKEM1024, 256, Some(192)),
+            (KxGroup::X448MLKEM1024, 256, Some(224)),
+        ] {
+            assert_eq!(group.security_bits(), bits);
+            assert_eq!(group.classical_security_bits(), classical_bits);
+        }
+
+        let unknown = KxGroup::new(rustls::NamedGroup::Unknown(0xfeff), b"unknown\0");
+        assert_eq!(unknown.security_bits(), 0);
+    }
+
+    #[test]
+    fn named_group_classification() {
+        for group in crate::hybrid_kx_groups() {
+            assert!(crate::named_group_is_post_quantum(group.name()));
+            assert!(crate::named_group_is_hybrid(group.name()));
+        }
+        for group in crate::pure_pq_kx_groups() {
+            assert!(crate::named_group_is_post_quantum(group.name()));
+            assert!(!crate::named_group_is_hybrid(group.name()));
+        }
+        for group in [rustls::NamedGroup::X25519, rustls::NamedGroup::secp256r1] {
+            assert!(!crate::named_group_is_post_quantum(group));
+            assert!(!crate::named_group_is_hybrid(group));
+        }
+    }
+
+    #[test]
+    fn wire_ids() {
+        assert_eq!(KxGroup::MLKEM512.to_wire_id(), 0x0200);
+        assert_eq!(KxGroup::MLKEM768.to_wire_id(), 0x0201);
+        assert_eq!(KxGroup::MLKEM1024.to_wire_id(), 0x0202);
+        assert_eq!(KxGroup::P256MLKEM768.to_wire_id(), 0x11eb);
+        assert_eq!(KxGroup::X25519MLKEM768.to_wire_id(), 0x11ec);
+
+        for group in crate::all_kx_groups() {
+            let id = u16::from(group.name());
+            assert_eq!(KxGroup::from_wire_id(id).unwrap().name(), group.name());
+        }
+        // X25519 is classical, so not provided by this crate
+        assert!(KxGroup::from_wire_id(0x001d).is_none());
+    }
+
+    #[test]
+    fn from_named_group() {
+        #[allow(deprecated)]
+        for group in crate::all_kx_groups().iter().chain([&crate::KYBER768]) {
+            match KxGroup::from_named_group(group.name()) {
+                Some(found) => assert_eq!(found.name(), group.name()),
+                None => assert_eq!(group.name(), crate::XWING.name()),
+            }
+        }
+        assert_eq!(
+            KxGroup::from_named_group(rustls::NamedGroup::X25519MLKEM768)
+                .unwrap()
+                .security_bits(),
+            192
+        );
+        assert!(KxGroup::from_named_group(rustls::NamedGroup::X25519).is_none());
+    }
+
+    #[test]
+    fn kem_component_name() {
+        assert_eq!(KxGroup::X25519MLKEM768.kem_component_name(), "mlkem768");
+        assert_eq!(KxGroup::P256MLKEM768.kem_component_name(), "mlkem768");
+        assert_eq!(KxGroup::P384MLKEM1024.kem_component_name(), "mlkem1024");
+        assert_eq!(KxGroup::X448MLKEM1024.kem_component_name(), "mlkem1024");
+        assert_eq!(KxGroup::MLKEM768.kem_component_name(), "mlkem768");
+        assert_eq!(KxGroup::HQC128.kem_component_name(), "hqc128");
+        assert_eq!(
+            KxGroup::MLKEM768
+                .with_custom_algorithm_name(b"MLKEM768\0")
+                .kem_component_name(),
+            "MLKEM768"
+        );
+    }
+
+    #[test]
+    fn ordering() {
+        assert!(KxGroup::MLKEM1024 > KxGroup::MLKEM768);
+        assert!(KxGroup::MLKEM768 > KxGroup::MLKEM512);
+        assert!(KxGroup::P384MLKEM1024 > KxGroup::MLKEM768);
+        assert_eq!(
+            KxGroup::MLKEM768.cmp(&KxGroup::MLKEM768.with_custom_algorithm_name(b"MLKEM768\0")),
+            std::cmp::Ordering::Equal
+        );
+
+        let mut groups = vec![
+            KxGroup::MLKEM512,
+            KxGroup::X25519MLKEM768,
+            KxGroup::MLKEM1024,
+            KxGroup::HQC128,
+            KxGroup::MLKEM768,
+            KxGroup::X448MLKEM1024,
+        ];
+        groups.sort();
+        assert_eq!(
+            groups,
+            [
+                KxGroup::HQC128,
+                KxGroup::MLKEM512,
+                KxGroup::X25519MLKEM768,
+                KxGroup::MLKEM768,
+                KxGroup::MLKEM1024,
+                KxGroup::X448MLKEM1024,
+            ]
+        );
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    #[cfg(feature = "serde")]
+    fn metadata_serde_roundtrip() {
+        for group in [
+            KxGroup::MLKEM512,
+            KxGroup::MLKEM768,
+            KxGroup::MLKEM1024,
+            KxGroup::P256MLKEM768,
+            KxGroup::P384MLKEM1024,
+            KxGroup::X448MLKEM1024,
+            KxGroup::HQC128,
+            KxGroup::BIKEL1,
+            KxGroup::FRODO640AES,
+            KxGroup::KYBER768,
+            KxGroup::X25519MLKEM768,
+        ] {
+            let metadata = group.to_metadata();
+            let json = serde_json::to_string(&metadata).unwrap();
+            assert_eq!(
+                serde_json::from_str::<crate::KxGroupMetadata>(&json).unwrap(),
+                metadata
+            );
+        }
+
+        assert_eq!(
+            serde_json::to_string(&KxGroup::X25519MLKEM768.to_metadata()).unwrap(),
+            r#"{"name":"X25519MLKEM768","named_group_id":4588,"is_hybrid":true,"security_bits":192}"#
+        );
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn fips_approved() {
+        for (group, approved) in [
+            (MLKEM512, true),
+            (MLKEM768, true),
+            (MLKEM1024, true),
+            (P256MLKEM768, true),
+            (P384MLKEM1024, true),
+            (X25519MLKEM768, false),
+            (X448MLKEM1024, false),
+            (XWING, false),
+            (HQC128, false),
+            (BIKEL1, false),
+            (FRODO640AES, false),
+            (crate::KYBER768, false),
+        ] {
+            assert_eq!(
+                crate::is_fips_approved(group.name()),
+                approved,
+                "{:?}",
+                group.name()
+            );
+        }
+
+        let names: Vec<_> = crate::fips_approved_groups()
+            .iter()
+            .map(|group| group.name())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                P384MLKEM1024.name(),
+                P256MLKEM768.name(),
+                MLKEM1024.name(),
+                MLKEM768.name(),
+                MLKEM512.name()
+            ]
+        );
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(format!("{}", KxGroup::X25519MLKEM768), "X25519MLKEM768");
+        assert_eq!(format!("{}", KxGroup::MLKEM768), "mlkem768");
+
+        let invalid_utf8 = KxGroup::new(rustls::NamedGroup::Unknown(0xfeff), b"\xff\0");
+        assert_eq!(format!("{invalid_utf8}"), "NamedGroup(0xfeff)");
+    }
+
+    #[test]
+    fn eq_and_hash() {
+        use std::collections::HashSet;
+
+        assert_ne!(KxGroup::MLKEM768, KxGroup::X25519MLKEM768);
+        assert_eq!(
+            KxGroup::MLKEM768,
+            KxGroup::MLKEM768.with_custom_algorithm_name(b"MLKEM768\0")
+        );
+
+        let groups: HashSet<KxGroup> = [
+            KxGroup::MLKEM768,
+            KxGroup::X25519MLKEM768,
+            KxGroup::MLKEM768.with_custom_algorithm_name(b"MLKEM768\0"),
+            KxGroup::X25519MLKEM768,
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(groups.len(), 2);
+    }
+
+    #[test]
+    fn kx_groups() {
+        assert_eq!(crate::all_kx_groups().len(), 11);
+        assert_eq!(crate::hybrid_kx_groups().len(), 5);
+        assert_eq!(crate::pure_pq_kx_groups().len(), 6);
+
+        // is_hybrid only depends on the named group
+        let is_hybrid =
+            |group: &&dyn SupportedKxGroup| KxGroup::new(group.name(), b"\0").is_hybrid();
+        assert!(crate::hybrid_kx_groups()
+            .iter()
+            .filter(|group| group.name() != XWING.name())
+            .all(is_hybrid));
+        assert!(!crate::pure_pq_kx_groups().iter().any(is_hybrid));
+    }
+
+    #[test]
+    fn kx_group_by_named_group() {
+        #[allow(deprecated)]
+        for group in crate::all_kx_groups().iter().chain([&crate::KYBER768]) {
+            let found = crate::kx_group_by_named_group(group.name()).unwrap();
+            assert_eq!(found.name(), group.name());
+        }
+        assert!(crate::kx_group_by_named_group(rustls::NamedGroup::X25519).is_none());
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn key_sizes() {
+        load_providers();
+
+        for group in [
+            KxGroup::MLKEM512,
+            KxGroup::MLKEM768,
+            KxGroup::MLKEM1024,
+            KxGroup::X25519MLKEM768,
+            KxGroup::P256MLKEM768,
+            KxGroup::P384MLKEM1024,
+            KxGroup::X448MLKEM1024,
+            KxGroup::HQC128,
+            KxGroup::BIKEL1,
+            KxGroup::FRODO640AES,
+            KxGroup::KYBER768,
+        ] {
+            let kx = group.start().unwrap();
+            assert_eq!(Some(kx.pub_key().len()), group.pub_key_size(), "{group}");
+            let completed = group.start_and_complete(kx.pub_key()).unwrap();
+            assert_eq!(
+                Some(completed.pub_key.len()),
+                group.ciphertext_size(),
+                "{group}"
+            );
+            assert_eq!(
+                Some(completed.secret.secret_bytes().len()),
+                group.shared_secret_size(),
+                "{group}"
+            );
+        }
+
+        let unknown = KxGroup::new(rustls::NamedGroup::Unknown(0xfeff), b"unknown\0");
+        assert_eq!(unknown.pub_key_size(), None);
+        assert_eq!(unknown.ciphertext_size(), None);
+        assert_eq!(unknown.shared_secret_size(), None);
+    }
+
+    #[test]
+    fn groups_at_security_level() {
+        let names = |min_bits| {
+            crate::groups_at_security_level(min_bits)
+                .iter()
+                .map(|group| group.name())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(names(0).len(), crate::all_kx_groups().len());
+
+        let at_least_192 = names(192);
+        assert!(!at_least_192.contains(&MLKEM512.name()));
+        assert!(!at_least_192.contains(&HQC128.name()));
+        assert!(at_least_192.contains(&MLKEM768.name()));
+        assert!(at_least_192.contains(&X25519MLKEM768.name()));
+        assert!(at_least_192.contains(&XWING.name()));
+
+        assert_eq!(
+            names(256),
+            [P384MLKEM1024.name(), X448MLKEM1024.name(), MLKEM1024.name()]
+        );
+    }
+
+    #[test]
+    fn kem_error() {
+        use crate::KemError;
+        use rustls::{Error, OtherError};
+
+        let kem_error = |err: Error| {
+            let Error::Other(OtherError(err)) = err else {
+                panic!("unexpected error: {err:?}");
+            };
+            err
+        };
+
+        let err = kem_error(MLKEM768.start_and_complete(&[]).err().unwrap());
+        assert!(
+            matches!(
+                err.downcast_ref::<KemError>(),
+                Some(KemError::InvalidPublicKey { algorithm, got_len: 0, .. }) if algorithm == "mlkem768"
+            ),
+            "{err:?}"
+        );
+
+        // Groups with unknown key sizes fail in OpenSSL
+        let unknown = KxGroup::new(rustls::NamedGroup::Unknown(0xfeff), b"unknown\0");
+        let err = kem_error(unknown.start_and_complete(&[]).err().unwrap());
+        assert!(
+            matches!(
+                err.downcast_ref::<KemError>(),
+                Some(KemError::Encapsulate { algorithm, .. }) if algorithm == "unknown"
+            ),
+            "{err:?}"
+        );
+    }
+
+    #[test]
+    fn classical_key_agreement_error() {
+        use crate::KemError;
+        use rustls::{Error, OtherError};
+
+        load_providers();
+        let kx = P256MLKEM768.start().unwrap();
+        let Some(Error::Other(OtherError(err))) = kx.complete_hybrid_component(&[4; 65]).err()
+        else {
+            panic!("expected a KemError");
+        };
+        assert!(
+            matches!(
+                err.downcast_ref::<KemError>(),
+                Some(KemError::ClassicalKeyAgreement { algorithm, .. }) if algorithm == "p256_mlkem768"
+            ),
+            "{err:?}"
+        );
+    }
+
+    #[test]
+    fn invalid_lengths() {
+        let err = MLKEM768.start_and_complete(&[0; 10]).err().unwrap();
+        assert!(
+            err.to_string()
+                .contains("invalid mlkem768 public key: expected 1184 bytes, got 10"),
+            "{err}"
+        );
+
+        load_providers();
+        let kx = MLKEM768.start().unwrap();
+        let err = kx.complete(&[0; 1089]).err().unwrap();
+        assert!(
+            err.to_string()
+                .contains("invalid mlkem768 ciphertext: expected 1088 bytes, got 1089"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn off_by_one_pub_keys() {
+        use crate::KemError;
+        use rustls::{Error, OtherError};
+
+        // The length is checked before calling OpenSSL, so this doesn't need the providers
+        for group in [
+            KxGroup::MLKEM512,
+            KxGroup::MLKEM768,
+            KxGroup::MLKEM1024,
+            KxGroup::P256MLKEM768,
+            KxGroup::P384MLKEM1024,
+            KxGroup::X448MLKEM1024,
+            KxGroup::HQC128,
+            KxGroup::BIKEL1,
+            KxGroup::FRODO640AES,
+            KxGroup::KYBER768,
+            KxGroup::X25519MLKEM768,
+        ] {
+            let expected_len = group.pub_key_size().unwrap();
+            for len in [expected_len - 1, expected_len + 1] {
+                let Some(Error::Other(OtherError(err))) =
+                    group.start_and_complete(&vec![0; len]).err()
+                else {
+                    panic!("{group} accepted a {len} byte public key");
+                };
+                assert!(
+                    matches!(
+                        err.downcast_ref::<KemError>(),
+                        Some(KemError::InvalidPublicKey { expected_len: Some(expected), got_len, .. })
+                            if *expected == expected_len && *got_len == len
+                    ),
+                    "{err:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn wrong_length_ciphertexts() {
+        load_providers();
+
+        for group in crate::ALL_KX_GROUPS
+            .iter()
+            .copied()
+            .chain([crate::KYBER768])
+        {
+            for len in [0, 1, 64 * 1024] {
+                let kx = group.start().unwrap();
+                let err = kx.complete(&vec![0; len]).err().unwrap();
+                let message = err.to_string();
+                assert!(message.contains("ciphertext"), "{message}");
+                assert!(message.contains(&format!("got {len}")), "{message}");
+            }
+        }
+    }
+
+    #[test]
+    fn mismatched_algorithms() {
+        load_providers();
+
+        let mlkem768 = MLKEM768.start().unwrap();
+        let mlkem1024 = MLKEM1024.start().unwrap();
+        let err = MLKEM768
+            .start_and_complete(mlkem1024.pub_key())
+            .err()
+            .unwrap();
+        assert!(
+            err.to_string()
+                .contains("invalid mlkem768 public key: expected 1184 bytes, got 1568"),
+            "{err}"
+        );
+        assert!(MLKEM1024.start_and_complete(mlkem768.pub_key()).is_err());
+
+        // A custom group has no known key size, so relies on OpenSSL rejecting the key
+        let custom = KxGroup::new(rustls::NamedGroup::Unknown(0xfeff), b"mlkem768\0");
+        if let Ok(completed) = custom.start_and_complete(mlkem1024.pub_key()) {
+            if let Ok(secret) = mlkem1024.complete(&completed.pub_key) {
+                assert_ne!(secret.secret_bytes(), completed.secret.secret_bytes());
+            }
+        }
+    }
+
+    #[test]
+    fn length_prefix() {
+        let pub_key = [0, 0, 0, 3, 1, 2, 3];
+        assert!(!KxGroup::MLKEM768.pub_key_has_length_prefix());
+        assert_eq!(
+            KxGroup::MLKEM768.strip_length_prefix(&pub_key).unwrap(),
+            pub_key
+        );
+
+        // The prefix isn't part of key shares, so the sizes are the same either way
+        let group = KxGroup::X25519MLKEM768;
+        assert_eq!(group.pub_key_size(), Some(1216));
+        assert_eq!(group.ciphertext_size(), Some(1120));
+        if cfg!(swapx25519hybrid) {
+            assert!(group.pub_key_has_length_prefix());
+            assert_eq!(group.strip_length_prefix(&pub_key).unwrap(), [1, 2, 3]);
+            assert!(group.strip_length_prefix(&[0, 0, 0, 4, 1]).is_err());
+            assert!(group.strip_length_prefix(&[0, 0, 0, 1, 1, 2]).is_err());
+            assert!(group.strip_length_prefix(&[0, 0]).is_err());
+        } else {
+            assert!(!group.pub_key_has_length_prefix());
+            assert_eq!(group.strip_length_prefix(&pub_key).unwrap(), pub_key);
+        }
+    }
+
+    #[test]
+    fn randomness_available() {
+        KxGroup::check_minimum_randomness_available().unwrap();
+        KxGroup::wait_for_entropy(std::time::Duration::ZERO).unwrap();
+    }
+
+    /// Checks the classical component agrees on a secret with ring's implementation of the same algorithm.
+    fn ring_classical_interop(
+        group: &dyn SupportedKxGroup,
+        algorithm: &'static ring::agreement::Algorithm,
+    ) {
+        use ring::agreement::{agree_ephemeral, EphemeralPrivateKey, UnparsedPublicKey};
+        use ring::rand::SystemRandom;
+
+        load_providers();
+
+        let our_kx = group.start().unwrap();
+        let (_group, our_key) = our_kx.hybrid_component().unwrap();
+        let our_key = our_key.to_vec();
+
+        let their_key = EphemeralPrivateKey::generate(algorithm, &SystemRandom::new()).unwrap();
+        let their_pub_key = their_key.compute_public_key().unwrap();
+        let their_secret = agree_ephemeral(
+            their_key,
+            &UnparsedPublicKey::new(algorithm, &our_key),
+            |secret| secret.to_vec(),
+        )
+        .unwrap();
+
+        let our_secret = our_kx
+            .complete_hybrid_component(their_pub_key.as_ref())
+            .unwrap();
+        assert_eq!(our_secret.secret_bytes(), their_secret);
+    }
+
+    #[test]
+    fn ring_interop() {
+        ring_classical_interop(X25519MLKEM768, &ring::agreement::X25519);
+        ring_classical_interop(P256MLKEM768, &ring::agreement::ECDH_P256);
+        ring_classical_interop(P384MLKEM1024, &ring::agreement::ECDH_P384);
+    }
+
     #[test]
     fn x25519_mlkem768_classical() {
         roundtrip_classical(X25519MLKEM768, rustls_openssl::kx_group::X25519);
     }
+
+    #[test]
+    fn p256_mlkem768() {
+        roundtrip(
+            P256MLKEM768,
+            rustls::crypto::aws_lc_rs::kx_group::SECP256R1MLKEM768,
+        );
+    }
+
+    #[test]
+    fn p256_mlkem768_classical() {
+        roundtrip_classical(P256MLKEM768, rustls_openssl::kx_group::SECP256R1);
+    }
+
+    #[test]
+    fn x448_mlkem1024() {
+        roundtrip(X448MLKEM1024, X448MLKEM1024);
+    }
+
+    #[test]
+    fn x448_mlkem1024_classical() {
+        use openssl::derive::Deriver;
+        use openssl::pkey::{Id, PKey};
+
+        load_providers();
+
+        let our_kx = X448MLKEM1024.start().unwrap();
+        let (group, our_key) = our_kx.hybrid_component().unwrap();
+        assert_eq!(group, rustls::NamedGroup::X448);
+
+        let their_key = PKey::generate_x448().unwrap();
+        let their_pub_key = their_key.raw_public_key().unwrap();
+        let our_key = PKey::public_key_from_raw_bytes(our_key, Id::X448).unwrap();
+        let mut deriver = Deriver::new(&their_key).unwrap();
+        deriver.set_peer(&our_key).unwrap();
+        let their_secret = deriver.derive_to_vec().unwrap();
+
+        let our_secret = our_kx.complete_hybrid_component(&their_pub_key).unwrap();
+        assert_eq!(our_secret.secret_bytes(), their_secret);
+    }
+
+    #[test]
+    fn p384_mlkem1024() {
+        roundtrip(P384MLKEM1024, P384MLKEM1024);
+    }
+
+    #[test]
+    fn p384_mlkem1024_classical() {
+        roundtrip_classical(P384MLKEM1024, rustls_openssl::kx_group::SECP384R1);
+    }
 }
@@ -0,0 +1,123 @@
+//! Counters of KEM operations.
+use crate::observe::Operation;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static METRICS: KemMetrics = KemMetrics::new();
+
+/// Returns the process-wide counters of operations performed by [`KxGroup`](crate::KxGroup)s.
+pub fn metrics() -> &'static KemMetrics {
+    &METRICS
+}
+
+/// Counters of key generation, encapsulation and decapsulation operations, across all algorithms.
+///
+/// Failures are counted separately from successes, so e.g `keygen_count` is the number of key pairs
+/// successfully generated.
+#[derive(Debug, Default)]
+pub struct KemMetrics {
+    /// The number of key pairs generated by [`SupportedKxGroup::start`](rustls::crypto::SupportedKxGroup::start).
+    pub keygen_count: AtomicU64,
+    /// The number of successful encapsulations by
+    /// [`SupportedKxGroup::start_and_complete`](rustls::crypto::SupportedKxGroup::start_and_complete).
+    pub encap_count: AtomicU64,
+    /// The number of successful decapsulations by
+    /// [`ActiveKeyExchange::complete`](rustls::crypto::ActiveKeyExchange::complete).
+    pub decap_count: AtomicU64,
+    /// The number of failed key generations.
+    pub keygen_failures: AtomicU64,
+    /// The number of failed encapsulations, including those of malformed public keys.
+    pub encap_failures: AtomicU64,
+    /// The number of failed decapsulations, including those of malformed ciphertexts.
+    pub decap_failures: AtomicU64,
+}
+
+impl KemMetrics {
+    /// Creates a set of counters, all zero.
+    pub const fn new() -> Self {
+        Self {
+            keygen_count: AtomicU64::new(0),
+            encap_count: AtomicU64::new(0),
+            decap_count: AtomicU64::new(0),
+            keygen_failures: AtomicU64::new(0),
+            encap_failures: AtomicU64::new(0),
+            decap_failures: AtomicU64::new(0),
+        }
+    }
+
+    /// Sets all counters to zero.
+    ///
+    /// This is intended for tests; operations on other threads may be counted before or after the reset.
+    pub fn reset(&self) {
+        for counter in [
+            &self.keygen_count,
+            &self.encap_count,
+            &self.decap_count,
+            &self.keygen_failures,
+            &self.encap_failures,
+            &self.decap_failures,
+        ] {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+
+    /// Counts an operation.
+    pub(crate) fn record(&self, operation: Operation, ok: bool) {
+        let counter = match (operation, ok) {
+            (Operation::Keygen, true) => &self.keygen_count,
+            (Operation::Encapsulate, true) => &self.encap_count,
+            (Operation::Decapsulate, true) => &self.decap_count,
+            (Operation::Keygen, false) => &self.keygen_failures,
+            (Operation::Encapsulate, false) => &self.encap_failures,
+            (Operation::Decapsulate, false) => &self.decap_failures,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{metrics, KemMetrics};
+    use crate::tests::load_providers;
+    use crate::MLKEM768;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    fn get(counter: &AtomicU64) -> u64 {
+        counter.load(Ordering::Relaxed)
+    }
+
+    #[test]
+    fn counters() {
+        load_providers();
+        let metrics = metrics();
+
+        // Other tests run concurrently, so the counters may increase by more than expected
+        let keygen_count = get(&metrics.keygen_count);
+        let encap_count = get(&metrics.encap_count);
+        let decap_count = get(&metrics.decap_count);
+        let encap_failures = get(&metrics.encap_failures);
+        let decap_failures = get(&metrics.decap_failures);
+
+        let kx = MLKEM768.start().unwrap();
+        let completed = MLKEM768.start_and_complete(kx.pub_key()).unwrap();
+        kx.complete(&completed.pub_key).unwrap();
+        assert!(MLKEM768.start_and_complete(&[0; 1]).is_err());
+        assert!(MLKEM768.start().unwrap().complete(&[0; 1]).is_err());
+
+        assert!(get(&metrics.keygen_count) >= keygen_count + 2);
+        assert!(get(&metrics.encap_count) > encap_count);
+        assert!(get(&metrics.decap_count) > decap_count);
+        assert!(get(&metrics.encap_failures) > encap_failures);
+        assert!(get(&metrics.decap_failures) > decap_failures);
+    }
+
+    #[test]
+    fn reset() {
+        let metrics = KemMetrics::new();
+        metrics.keygen_count.fetch_add(3, Ordering::Relaxed);
+        metrics.decap_failures.fetch_add(1, Ordering::Relaxed);
+
+        metrics.reset();
+        assert_eq!(get(&metrics.keygen_count), 0);
+        assert_eq!(get(&metrics.decap_failures), 0);
+    }
+}
@@ -0,0 +1,194 @@
+//! Instrumentation of KEM operations.
+use crate::kem::{KeyExchange, KxGroup};
+use rustls::crypto::{ActiveKeyExchange, CompletedKeyExchange, SharedSecret};
+use std::fmt::Display;
+
+/// A KEM operation performed by a [`KxGroup`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Operation {
+    Keygen,
+    Encapsulate,
+    Decapsulate,
+}
+
+impl Operation {
+    /// The operation name used in log messages.
+    #[cfg(feature = "log")]
+    fn name(self) -> &'static str {
+        match self {
+            Operation::Keygen => "keygen",
+            Operation::Encapsulate => "encapsulate",
+            Operation::Decapsulate => "decapsulate",
+        }
+    }
+
+    /// The noun used in log messages for failures.
+    #[cfg(feature = "log")]
+    fn noun(self) -> &'static str {
+        match self {
+            Operation::Keygen => "key generation",
+            Operation::Encapsulate => "encapsulation",
+            Operation::Decapsulate => "decapsulation",
+        }
+    }
+}
+
+/// The output of an [`Operation`], whose length is logged.
+pub(crate) trait Output {
+    /// The log field name and value for the output length, e.g `("pub_key_len", 1184)`.
+    #[cfg_attr(not(feature = "log"), allow(dead_code))]
+    fn len_field(&self) -> (&'static str, usize);
+}
+
+impl Output for Box<KeyExchange> {
+    fn len_field(&self) -> (&'static str, usize) {
+        ("pub_key_len", self.pub_key().len())
+    }
+}
+
+impl Output for CompletedKeyExchange {
+    fn len_field(&self) -> (&'static str, usize) {
+        ("ciphertext_len", self.pub_key.len())
+    }
+}
+
+impl Output for SharedSecret {
+    fn len_field(&self) -> (&'static str, usize) {
+        ("secret_len", self.secret_bytes().len())
+    }
+}
+
+/// Runs `f`, which performs `operation` for `group`, instrumenting it according to the enabled features.
+///
+/// With the `tracing` feature, `f` runs in a `kem_keygen`, `kem_encapsulate` or `kem_decapsulate` debug span with
+/// the algorithm name, and the duration in microseconds is recorded on the span when `f` returns.
+///
+/// The operation is always counted in [`metrics`](crate::metrics).
+///
+/// With the `log` feature, a debug message is logged before and after `f`, and a warning if it fails.
+#[cfg_attr(
+    not(any(feature = "tracing", feature = "log")),
+    allow(unused_variables)
+)]
+pub(crate) fn observe<T: Output, E: Display>(
+    operation: Operation,
+    group: &KxGroup,
+    f: impl FnOnce() -> Result<T, E>,
+) -> Result<T, E> {
+    #[cfg(feature = "tracing")]
+    let (_span, start) = {
+        let algorithm = group.algorithm();
+        let duration_us = tracing::field::Empty;
+        let span = match operation {
+            Operation::Keygen => tracing::debug_span!("kem_keygen", %algorithm, duration_us),
+            Operation::Encapsulate => {
+                tracing::debug_span!("kem_encapsulate", %algorithm, duration_us)
+            }
+            Operation::Decapsulate => {
+                tracing::debug_span!("kem_decapsulate", %algorithm, duration_us)
+            }
+        };
+        (span.entered(), std::time::Instant::now())
+    };
+
+    #[cfg(feature = "log")]
+    log::debug!("{}: algorithm={}", operation.name(), group.algorithm());
+
+    let result = f();
+    crate::metrics::metrics().record(operation, result.is_ok());
+
+    #[cfg(feature = "log")]
+    match &result {
+        Ok(output) => {
+            let (field, len) = output.len_field();
+            log::debug!(
+                "{}: algorithm={}, {field}={len}",
+                operation.name(),
+                group.algorithm()
+            );
+        }
+        Err(e) => log::warn!("{} failed: {e}", operation.noun()),
+    }
+
+    #[cfg(feature = "tracing")]
+    {
+        let duration_us = start.elapsed().as_micros() as u64;
+        _span.record("duration_us", duration_us);
+        tracing::debug!(duration_us, ok = result.is_ok(), "completed");
+    }
+
+    result
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use crate::tests::load_providers;
+    use crate::MLKEM768;
+    use tracing_test::traced_test;
+
+    #[test]
+    #[traced_test]
+    fn spans() {
+        load_providers();
+
+        let kx = MLKEM768.start().unwrap();
+        let completed = MLKEM768.start_and_complete(kx.pub_key()).unwrap();
+        kx.complete(&completed.pub_key).unwrap();
+
+        for span in ["kem_keygen", "kem_encapsulate", "kem_decapsulate"] {
+            assert!(
+                logs_contain(&format!("{span}{{algorithm=mlkem768")),
+                "{span}"
+            );
+        }
+        assert!(logs_contain("duration_us="));
+    }
+}
+
+#[cfg(all(test, feature = "log"))]
+mod log_tests {
+    use crate::tests::load_providers;
+    use crate::MLKEM768;
+    use std::io::{self, Write};
+    use std::sync::{Arc, Mutex};
+
+    /// A log target that appends to a shared buffer.
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Capture {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn messages() {
+        load_providers();
+        let capture = Capture::default();
+        env_logger::Builder::new()
+            .filter_module("rustls_liboqs", log::LevelFilter::Debug)
+            .format(|buf, record| writeln!(buf, "{} {}", record.level(), record.args()))
+            .target(env_logger::Target::Pipe(Box::new(capture.clone())))
+            .init();
+
+        let kx = MLKEM768.start().unwrap();
+        let completed = MLKEM768.start_and_complete(kx.pub_key()).unwrap();
+        kx.complete(&completed.pub_key).unwrap();
+
+        let output = String::from_utf8(capture.0.lock().unwrap().clone()).unwrap();
+        for message in [
+            "DEBUG keygen: algorithm=mlkem768\n",
+            "DEBUG keygen: algorithm=mlkem768, pub_key_len=1184\n",
+            "DEBUG encapsulate: algorithm=mlkem768, ciphertext_len=1088\n",
+            "DEBUG decapsulate: algorithm=mlkem768, secret_len=32\n",
+        ] {
+            assert!(output.contains(message), "{message:?} not in {output}");
+        }
+    }
+}
@@ -0,0 +1,84 @@
+//! Parsing key exchange groups from their names.
+use crate::kem::KxGroup;
+use std::fmt;
+use std::str::FromStr;
+
+/// The groups that can be parsed, by exported constant name.
+///
+/// Groups can also be parsed by their OpenSSL algorithm name, e.g `p256_mlkem768`, or IANA name.
+#[allow(deprecated)]
+const GROUPS: &[(&str, KxGroup)] = &[
+    ("MLKEM512", KxGroup::MLKEM512),
+    ("MLKEM768", KxGroup::MLKEM768),
+    ("MLKEM1024", KxGroup::MLKEM1024),
+    ("P256MLKEM768", KxGroup::P256MLKEM768),
+    ("SecP256r1MLKEM768", KxGroup::P256MLKEM768),
+    ("P384MLKEM1024", KxGroup::P384MLKEM1024),
+    ("SecP384r1MLKEM1024", KxGroup::P384MLKEM1024),
+    ("X448MLKEM1024", KxGroup::X448MLKEM1024),
+    ("X25519MLKEM768", KxGroup::X25519MLKEM768),
+    ("HQC128", KxGroup::HQC128),
+    ("BIKEL1", KxGroup::BIKEL1),
+    ("FRODO640AES", KxGroup::FRODO640AES),
+    ("KYBER768", KxGroup::KYBER768),
+];
+
+/// An error returned when parsing an unknown key exchange group name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    name: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown key exchange group: {}", self.name)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl FromStr for KxGroup {
+    type Err = ParseError;
+
+    /// Parses a group from its name, ignoring case.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        GROUPS
+            .iter()
+            .find(|(name, group)| {
+                name.eq_ignore_ascii_case(s) || group.algorithm().eq_ignore_ascii_case(s)
+            })
+            .map(|(_, group)| *group)
+            .ok_or_else(|| ParseError {
+                name: s.to_string(),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ParseError;
+    use crate::KxGroup;
+
+    #[test]
+    fn parse_exported_constants() {
+        for (name, group) in super::GROUPS {
+            assert_eq!(name.parse::<KxGroup>().unwrap(), *group);
+            assert_eq!(name.to_lowercase().parse::<KxGroup>().unwrap(), *group);
+        }
+        assert_eq!(
+            "p256_mlkem768".parse::<KxGroup>().unwrap(),
+            KxGroup::P256MLKEM768
+        );
+    }
+
+    #[test]
+    fn parse_unknown() {
+        assert_eq!(
+            "X25519".parse::<KxGroup>(),
+            Err(ParseError {
+                name: "X25519".to_string()
+            })
+        );
+        assert!("".parse::<KxGroup>().is_err());
+    }
+}
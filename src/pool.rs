@@ -0,0 +1,212 @@
+//! Pre-generation of key exchange key pairs.
+use rustls::crypto::{ActiveKeyExchange, CompletedKeyExchange, SupportedKxGroup};
+use rustls::{Error, NamedGroup, ProtocolVersion};
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+
+/// A key exchange group that hands out key pairs generated ahead of time by a background thread.
+///
+/// Post-quantum key generation can dominate the cost of a handshake on a busy server. The pool keeps up to
+/// `capacity` key pairs ready, so [`KxGroupPool::take`] usually doesn't need to generate one. If the pool is
+/// empty, a key pair is generated synchronously instead.
+///
+/// The pool implements [`SupportedKxGroup`], so it can be used in place of the group it wraps. Each key pair is
+/// only ever handed out once.
+///
+/// If the background thread fails to generate a key pair it stops, and key pairs are generated synchronously
+/// from then on, returning the error to the caller.
+pub struct KxGroupPool {
+    group: &'static dyn SupportedKxGroup,
+    shared: Arc<Shared>,
+    filler: Option<JoinHandle<()>>,
+}
+
+/// State shared with the background thread.
+struct Shared {
+    state: Mutex<State>,
+    /// Signalled when a key pair is taken or the pool is dropped.
+    taken: Condvar,
+    capacity: usize,
+}
+
+struct State {
+    keys: VecDeque<Box<dyn ActiveKeyExchange>>,
+    shutdown: bool,
+}
+
+impl KxGroupPool {
+    /// Creates a pool of up to `capacity` key pairs for `group`, and starts a thread to fill it.
+    pub fn new(group: &'static dyn SupportedKxGroup, capacity: usize) -> Self {
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State {
+                keys: VecDeque::with_capacity(capacity),
+                shutdown: false,
+            }),
+            taken: Condvar::new(),
+            capacity,
+        });
+        let filler = {
+            let shared = Arc::clone(&shared);
+            thread::spawn(move || shared.fill(group))
+        };
+        Self {
+            group,
+            shared,
+            filler: Some(filler),
+        }
+    }
+
+    /// Takes a pre-generated key pair, or generates one if the pool is empty.
+    #[must_use = "key exchange will not complete if this result is dropped"]
+    pub fn take(&self) -> Result<Box<dyn ActiveKeyExchange>, Error> {
+        let key = self.shared.lock().keys.pop_front();
+        match key {
+            Some(key) => {
+                self.shared.taken.notify_one();
+                Ok(key)
+            }
+            None => self.group.start(),
+        }
+    }
+
+    /// Returns the number of key pairs ready to be taken.
+    pub fn len(&self) -> usize {
+        self.shared.lock().keys.len()
+    }
+
+    /// Returns true if no key pairs are ready to be taken.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Shared {
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        // Key pairs are only pushed and popped while locked, so the state is valid even if a thread panicked.
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Keeps the pool filled until it's dropped or key generation fails.
+    fn fill(&self, group: &dyn SupportedKxGroup) {
+        loop {
+            {
+                let mut state = self.lock();
+                while state.keys.len() >= self.capacity && !state.shutdown {
+                    state = self
+                        .taken
+                        .wait(state)
+                        .unwrap_or_else(|poisoned| poisoned.into_inner());
+                }
+                if state.shutdown {
+                    return;
+                }
+            }
+
+            // Generate without holding the lock, so that callers can take key pairs meanwhile.
+            let Ok(key) = group.start() else {
+                return;
+            };
+            self.lock().keys.push_back(key);
+        }
+    }
+}
+
+impl Drop for KxGroupPool {
+    fn drop(&mut self) {
+        self.shared.lock().shutdown = true;
+        self.shared.taken.notify_one();
+        if let Some(filler) = self.filler.take() {
+            let _ = filler.join();
+        }
+    }
+}
+
+impl fmt::Debug for KxGroupPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KxGroupPool")
+            .field("group", &self.group.name())
+            .field("capacity", &self.shared.capacity)
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl SupportedKxGroup for KxGroupPool {
+    fn start(&self) -> Result<Box<dyn ActiveKeyExchange>, Error> {
+        self.take()
+    }
+
+    fn start_and_complete(&self, peer_pub_key: &[u8]) -> Result<CompletedKeyExchange, Error> {
+        self.group.start_and_complete(peer_pub_key)
+    }
+
+    fn ffdhe_group(&self) -> Option<rustls::ffdhe_groups::FfdheGroup<'static>> {
+        self.group.ffdhe_group()
+    }
+
+    fn name(&self) -> NamedGroup {
+        self.group.name()
+    }
+
+    fn fips(&self) -> bool {
+        self.group.fips()
+    }
+
+    fn usable_for_version(&self, version: ProtocolVersion) -> bool {
+        self.group.usable_for_version(version)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::KxGroupPool;
+    use rustls::crypto::aws_lc_rs::kx_group::X25519;
+    use rustls::crypto::SupportedKxGroup;
+    use std::time::{Duration, Instant};
+
+    /// Waits for the background thread to fill the pool.
+    fn wait_until_full(pool: &KxGroupPool, capacity: usize) {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while pool.len() < capacity {
+            assert!(Instant::now() < deadline, "pool wasn't filled");
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn fills_to_capacity() {
+        let pool = KxGroupPool::new(X25519, 4);
+        wait_until_full(&pool, 4);
+        std::thread::sleep(Duration::from_millis(10));
+        assert_eq!(pool.len(), 4);
+    }
+
+    #[test]
+    fn takes_unique_keys() {
+        let pool = KxGroupPool::new(X25519, 2);
+        wait_until_full(&pool, 2);
+
+        let first = pool.take().unwrap();
+        let second = pool.take().unwrap();
+        assert_ne!(first.pub_key(), second.pub_key());
+        assert_eq!(first.group(), X25519.name());
+
+        // The pool is refilled after key pairs are taken
+        wait_until_full(&pool, 2);
+    }
+
+    #[test]
+    fn zero_capacity_generates_synchronously() {
+        let pool = KxGroupPool::new(X25519, 0);
+        assert!(pool.is_empty());
+
+        let kx = pool.start().unwrap();
+        let completed = pool.start_and_complete(kx.pub_key()).unwrap();
+        let secret = kx.complete(&completed.pub_key).unwrap();
+        assert_eq!(secret.secret_bytes(), completed.secret.secret_bytes());
+    }
+}
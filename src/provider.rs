@@ -0,0 +1,211 @@
+//! Helpers for the OpenSSL providers used by this crate.
+use crate::error::KemError;
+use crate::ffi;
+use crate::version::OqsProviderVersion;
+use openssl::error::ErrorStack;
+use openssl::provider::Provider;
+use std::ffi::CStr;
+
+/// The name of the [oqsprovider](https://github.com/open-quantum-safe/oqs-provider) OpenSSL provider.
+const OQS_PROVIDER: &CStr = c"oqsprovider";
+
+/// The environment variable that overrides where oqsprovider is loaded from.
+const OQS_PROVIDER_PATH: &str = "OQS_PROVIDER_PATH";
+
+/// Loads oqsprovider into the default OpenSSL library context.
+///
+/// If `path` is `Some`, the provider module is loaded from that filesystem path, e.g.
+/// `/opt/oqs/lib/ossl-modules/oqsprovider.so`. Otherwise the path is taken from the `OQS_PROVIDER_PATH`
+/// environment variable if set, falling back to OpenSSL's default module search.
+///
+/// With the `bundled` feature, oqsprovider is statically linked into this crate and registered as a built-in
+/// provider, so it's always found by the default search.
+///
+/// Note that OpenSSL names a provider loaded from a path after that path, so
+/// [`check_oqs_provider_available`] only recognises oqsprovider when loaded from the default search.
+pub fn load_oqs_provider(path: Option<&str>) -> Result<Provider, ErrorStack> {
+    let provider = match path
+        .map(str::to_owned)
+        .or_else(|| std::env::var(OQS_PROVIDER_PATH).ok())
+    {
+        Some(path) => Provider::load(None, &path),
+        None => {
+            #[cfg(feature = "bundled")]
+            register_bundled_provider();
+            Provider::load(None, "oqsprovider")
+        }
+    }?;
+    // Loading can leave spurious errors on the error stack
+    let _ = ErrorStack::get();
+    Ok(provider)
+}
+
+/// Registers the statically linked oqsprovider with the default OpenSSL library context.
+#[cfg(feature = "bundled")]
+fn register_bundled_provider() {
+    static REGISTER: std::sync::Once = std::sync::Once::new();
+    REGISTER.call_once(|| {
+        openssl_sys::init();
+        // Registration only fails on allocation failure, which loading the provider will report.
+        unsafe {
+            ffi::OSSL_PROVIDER_add_builtin(
+                std::ptr::null_mut(),
+                OQS_PROVIDER.as_ptr(),
+                ffi::oqs_provider_init,
+            );
+        }
+    });
+}
+
+/// Keeps the default and oqsprovider OpenSSL providers loaded until dropped.
+///
+/// Providers are reference counted by OpenSSL, so dropping the guard only unloads the providers if nothing
+/// else has loaded them.
+#[doc(alias = "ProviderBundle")]
+pub struct OqsProviderGuard {
+    // Fields are dropped in declaration order, so oqsprovider is unloaded before the default provider it uses.
+    _oqs: Provider,
+    _default: Provider,
+}
+
+impl OqsProviderGuard {
+    /// Loads the default and oqsprovider providers into the default OpenSSL library context.
+    ///
+    /// oqsprovider is loaded with [`load_oqs_provider`], so the `OQS_PROVIDER_PATH` environment variable is
+    /// respected.
+    pub fn load() -> Result<Self, ErrorStack> {
+        Self::load_oqs(None)
+    }
+
+    /// Loads the default provider, and oqsprovider from the filesystem path `path`, e.g.
+    /// `/opt/oqs/lib/ossl-modules/oqsprovider.so`.
+    pub fn load_with_oqs_path(path: &str) -> Result<Self, ErrorStack> {
+        Self::load_oqs(Some(path))
+    }
+
+    fn load_oqs(path: Option<&str>) -> Result<Self, ErrorStack> {
+        let default = Provider::load(None, "default")?;
+        let oqs = load_oqs_provider(path)?;
+        Ok(Self {
+            _oqs: oqs,
+            _default: default,
+        })
+    }
+
+    /// Returns the version of the loaded oqsprovider, e.g `"0.7.0"`, if it reports one.
+    ///
+    /// Unlike [`oqs_provider_version`](crate::ffi::oqs_provider_version), this works when oqsprovider was loaded
+    /// from a path.
+    pub fn oqs_version(&self) -> Option<String> {
+        ffi::loaded_provider_version(&self._oqs)
+    }
+
+    /// Runs `f` with the providers loaded, unloading them afterwards.
+    ///
+    /// The providers are held by a guard, so they're also unloaded if `f` panics. As with [`OqsProviderGuard::load`],
+    /// providers loaded elsewhere in the process, e.g by tests running in parallel, stay loaded.
+    #[doc(alias = "with_providers")]
+    pub fn with<T>(f: impl FnOnce() -> T) -> Result<T, ErrorStack> {
+        let _guard = Self::load()?;
+        Ok(f())
+    }
+}
+
+/// Returns the version of the loaded oqsprovider, or `None` if it isn't loaded or its version can't be parsed.
+///
+/// See [`ffi::oqs_provider_version`] for the version string.
+pub fn oqs_provider_version() -> Option<OqsProviderVersion> {
+    ffi::oqs_provider_version()?.parse().ok()
+}
+
+/// Checks that oqsprovider has been loaded into the default OpenSSL library context.
+///
+/// Key generation fails with an opaque OpenSSL error if the provider isn't loaded, so [`crate::KxGroup`]
+/// uses this check to return a more helpful error. Applications can also call it at startup, to fail early with
+/// [`KemError::ProviderNotLoaded`] rather than on the first handshake.
+#[doc(alias = "require_oqs_provider")]
+pub fn check_oqs_provider_available() -> Result<(), KemError> {
+    check_provider_available(OQS_PROVIDER)
+}
+
+fn check_provider_available(name: &CStr) -> Result<(), KemError> {
+    if ffi::provider_available(name) {
+        Ok(())
+    } else {
+        Err(KemError::ProviderNotLoaded {
+            name: name.to_string_lossy().into_owned(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_provider_available, load_oqs_provider, OqsProviderGuard};
+    use crate::KemError;
+
+    #[test]
+    fn provider_not_loaded() {
+        let err = check_provider_available(c"notaprovider").unwrap_err();
+        assert!(
+            matches!(&err, KemError::ProviderNotLoaded { name } if name == "notaprovider"),
+            "{err:?}"
+        );
+        assert_eq!(
+            err.to_string(),
+            "notaprovider is not loaded; call openssl::provider::Provider::load(None, \"notaprovider\") before using \
+             post-quantum groups, and set OPENSSL_MODULES if it isn't installed in OpenSSL's default module directory"
+        );
+    }
+
+    #[test]
+    fn provider_loaded() {
+        let _provider = openssl::provider::Provider::load(None, "default").unwrap();
+        assert!(check_provider_available(c"default").is_ok());
+    }
+
+    #[test]
+    fn guard_with() {
+        let available = OqsProviderGuard::with(|| check_provider_available(c"oqsprovider").is_ok());
+        assert!(available.unwrap());
+    }
+
+    #[test]
+    fn guard_with_panic() {
+        let result = std::panic::catch_unwind(|| OqsProviderGuard::with(|| panic!("in f")));
+        assert!(result.is_err());
+
+        let available = OqsProviderGuard::with(|| check_provider_available(c"oqsprovider").is_ok());
+        assert!(available.unwrap());
+    }
+
+    #[test]
+    fn load_from_default_search() {
+        let _default = openssl::provider::Provider::load(None, "default").unwrap();
+        let _oqs = load_oqs_provider(None).unwrap();
+        assert!(check_provider_available(c"oqsprovider").is_ok());
+    }
+
+    #[test]
+    fn oqs_version() {
+        let guard = OqsProviderGuard::load().unwrap();
+        let version = guard.oqs_version().unwrap();
+        assert!(
+            version.split('.').next().unwrap().parse::<u32>().is_ok(),
+            "{version}"
+        );
+    }
+
+    #[test]
+    fn parsed_oqs_version() {
+        let guard = OqsProviderGuard::load().unwrap();
+        let version = super::oqs_provider_version().unwrap();
+        let raw = guard.oqs_version().unwrap();
+        assert!(raw.starts_with(&version.to_string()), "{raw}");
+    }
+
+    #[test]
+    fn load_from_missing_path() {
+        assert!(OqsProviderGuard::load_with_oqs_path("/nonexistent/oqsprovider.so").is_err());
+        assert!(load_oqs_provider(Some("/nonexistent/oqsprovider.so")).is_err());
+    }
+}
@@ -0,0 +1,298 @@
+//! Serialization of encoded public keys.
+use crate::ffi::{PKeyRefExt, PkeyExt};
+use crate::kem::KxGroup;
+use openssl::base64;
+use openssl::error::ErrorStack;
+use openssl::pkey::{PKey, Public};
+use std::fmt;
+
+/// PEM bodies are wrapped at 64 characters, as required by RFC 7468.
+const PEM_LINE_LEN: usize = 64;
+
+/// A borrowed view of an encoded public key for a [`KxGroup`], e.g the output of
+/// [`ActiveKeyExchange::pub_key`](rustls::crypto::ActiveKeyExchange::pub_key).
+///
+/// This can be serialized to make public keys easier to save, compare and share:
+///
+/// ```no_run
+/// use rustls::crypto::SupportedKxGroup;
+/// use rustls_liboqs::{KxGroup, PubKey};
+///
+/// let kx = KxGroup::MLKEM768.start().unwrap();
+/// let pem = PubKey::new(kx.pub_key(), KxGroup::MLKEM768).to_pem();
+/// assert!(pem.starts_with("-----BEGIN MLKEM768 PUBLIC KEY-----\n"));
+/// assert_eq!(PubKey::from_pem(&pem, &KxGroup::MLKEM768).unwrap(), kx.pub_key());
+/// ```
+#[derive(Debug, Copy, Clone)]
+pub struct PubKey<'a>(&'a [u8], KxGroup);
+
+impl<'a> PubKey<'a> {
+    /// Creates a view of the encoded public key `bytes` for `group`.
+    pub fn new(bytes: &'a [u8], group: KxGroup) -> Self {
+        Self(bytes, group)
+    }
+
+    /// Returns the encoded public key.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.0
+    }
+
+    /// Returns the group of the public key.
+    pub fn group(&self) -> KxGroup {
+        self.1
+    }
+
+    /// Encodes the public key as PEM, labelled with the upper case algorithm name, e.g `MLKEM768 PUBLIC KEY`.
+    ///
+    /// The PEM contains the encoded public key as sent in TLS key shares, not a SubjectPublicKeyInfo.
+    pub fn to_pem(&self) -> String {
+        let label = pem_label(&self.1);
+        let body = base64::encode_block(self.0);
+
+        let mut pem = format!("-----BEGIN {label}-----\n");
+        for line in body.as_bytes().chunks(PEM_LINE_LEN) {
+            // base64 is ASCII, so every chunk is valid UTF-8
+            pem.push_str(std::str::from_utf8(line).expect("base64 is ASCII"));
+            pem.push('\n');
+        }
+        pem.push_str(&format!("-----END {label}-----\n"));
+        pem
+    }
+
+    /// Decodes a public key for `group` from PEM produced by [`PubKey::to_pem`].
+    ///
+    /// The label must match the group, and the key must have the expected length if it's known.
+    pub fn from_pem(pem: &str, group: &KxGroup) -> Result<Vec<u8>, PemError> {
+        let label = pem_label(group);
+        let begin = format!("-----BEGIN {label}-----");
+        let end = format!("-----END {label}-----");
+
+        let pem = pem.trim();
+        let body = pem
+            .strip_prefix(&begin)
+            .and_then(|rest| rest.strip_suffix(&end))
+            .ok_or(PemError::MissingLabel { label })?;
+        let body: String = body.chars().filter(|c| !c.is_ascii_whitespace()).collect();
+        let bytes = base64::decode_block(&body).map_err(PemError::InvalidBase64)?;
+
+        match group.pub_key_size() {
+            Some(expected_len) if bytes.len() != expected_len => Err(PemError::InvalidLength {
+                expected_len,
+                got_len: bytes.len(),
+            }),
+            _ => Ok(bytes),
+        }
+    }
+
+    /// Encodes the public key as a DER SubjectPublicKeyInfo, with the algorithm's OID.
+    ///
+    /// The encoding is done by the OpenSSL provider of the algorithm, so the result can be read by the OpenSSL
+    /// command line tools, e.g `openssl pkey -pubin -inform DER`. This fails if the provider isn't loaded, the
+    /// public key is malformed, or the provider doesn't define an OID for the algorithm.
+    pub fn to_der(&self) -> Result<Vec<u8>, DerError> {
+        PKey::<Public>::from_encoded_public_key(self.0, self.1.algorithm_name())
+            .and_then(|pkey| pkey.public_key_to_der())
+            .map_err(DerError::Encode)
+    }
+
+    /// Decodes a public key for `group` from a DER SubjectPublicKeyInfo, e.g produced by [`PubKey::to_der`].
+    pub fn from_der(der: &[u8], group: &KxGroup) -> Result<Vec<u8>, DerError> {
+        const OSSL_PKEY_PARAM_ENCODED_PUB_KEY: &[u8] = b"encoded-pub-key\0";
+
+        let pkey = PKey::public_key_from_der(der).map_err(DerError::Decode)?;
+        if !pkey.is_algorithm(group.algorithm_name()) {
+            return Err(DerError::WrongAlgorithm {
+                expected: group.algorithm(),
+            });
+        }
+        pkey.get_octet_string_param(OSSL_PKEY_PARAM_ENCODED_PUB_KEY)
+            .map_err(DerError::Decode)
+    }
+}
+
+fn pem_label(group: &KxGroup) -> String {
+    format!("{} PUBLIC KEY", group.algorithm().to_uppercase())
+}
+
+/// An error returned when decoding a PEM public key.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum PemError {
+    /// The PEM doesn't have the BEGIN and END lines for the expected label.
+    MissingLabel {
+        /// The expected label, e.g `MLKEM768 PUBLIC KEY`.
+        label: String,
+    },
+    /// The PEM body isn't valid base64.
+    InvalidBase64(ErrorStack),
+    /// The decoded public key has the wrong length for the group.
+    InvalidLength {
+        /// The expected length of the public key.
+        expected_len: usize,
+        /// The length of the decoded public key.
+        got_len: usize,
+    },
+}
+
+impl fmt::Display for PemError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PemError::MissingLabel { label } => {
+                write!(f, "PEM is missing {label} BEGIN and END lines")
+            }
+            PemError::InvalidBase64(source) => write!(f, "invalid base64 in PEM: {source}"),
+            PemError::InvalidLength {
+                expected_len,
+                got_len,
+            } => write!(
+                f,
+                "invalid public key in PEM: expected {expected_len} bytes, got {got_len}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PemError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PemError::InvalidBase64(source) => Some(source),
+            PemError::MissingLabel { .. } | PemError::InvalidLength { .. } => None,
+        }
+    }
+}
+
+/// An error returned when encoding or decoding a DER public key.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum DerError {
+    /// OpenSSL failed to encode the public key.
+    Encode(ErrorStack),
+    /// OpenSSL failed to decode the SubjectPublicKeyInfo.
+    Decode(ErrorStack),
+    /// The SubjectPublicKeyInfo is for a different algorithm to the group.
+    WrongAlgorithm {
+        /// The OpenSSL algorithm name of the group.
+        expected: String,
+    },
+}
+
+impl fmt::Display for DerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DerError::Encode(source) => write!(f, "failed to encode public key as DER: {source}"),
+            DerError::Decode(source) => write!(f, "failed to decode DER public key: {source}"),
+            DerError::WrongAlgorithm { expected } => {
+                write!(f, "DER public key is not a {expected} public key")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DerError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DerError::Encode(source) | DerError::Decode(source) => Some(source),
+            DerError::WrongAlgorithm { .. } => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DerError, PemError, PubKey};
+    use crate::tests::load_providers;
+    use crate::KxGroup;
+    use rustls::crypto::SupportedKxGroup;
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    #[test]
+    fn pem_roundtrip() {
+        // Cover lengths that do and don't need base64 padding
+        for (group, len) in [
+            (KxGroup::MLKEM512, 800),
+            (KxGroup::MLKEM768, 1184),
+            (KxGroup::X25519MLKEM768, 1216),
+            (KxGroup::P256MLKEM768, 1249),
+        ] {
+            let bytes: Vec<u8> = (0..len).map(|i| (i * 7 % 256) as u8).collect();
+            let pem = PubKey::new(&bytes, group).to_pem();
+            assert!(pem.lines().all(|line| line.len() <= 64));
+            assert_eq!(PubKey::from_pem(&pem, &group).unwrap(), bytes);
+        }
+
+        let pem = PubKey::new(&[1; 800], KxGroup::MLKEM512).to_pem();
+        assert!(pem.starts_with("-----BEGIN MLKEM512 PUBLIC KEY-----\n"));
+        assert!(pem.ends_with("-----END MLKEM512 PUBLIC KEY-----\n"));
+    }
+
+    #[test]
+    fn pem_errors() {
+        let pem = PubKey::new(&[1; 800], KxGroup::MLKEM512).to_pem();
+        assert!(matches!(
+            PubKey::from_pem(&pem, &KxGroup::MLKEM768),
+            Err(PemError::MissingLabel { .. })
+        ));
+
+        let pem = PubKey::new(&[1; 10], KxGroup::MLKEM512).to_pem();
+        assert!(matches!(
+            PubKey::from_pem(&pem, &KxGroup::MLKEM512),
+            Err(PemError::InvalidLength {
+                expected_len: 800,
+                got_len: 10
+            })
+        ));
+
+        let pem = "-----BEGIN MLKEM512 PUBLIC KEY-----\n!!!!\n-----END MLKEM512 PUBLIC KEY-----\n";
+        assert!(matches!(
+            PubKey::from_pem(pem, &KxGroup::MLKEM512),
+            Err(PemError::InvalidBase64(_))
+        ));
+    }
+
+    /// Checks that the OpenSSL command line tool can parse a DER public key, using the same providers as the tests.
+    fn openssl_pkey_parses(der: &[u8]) -> bool {
+        let oqs_provider =
+            std::env::var("OQS_PROVIDER_PATH").unwrap_or_else(|_| "oqsprovider".to_string());
+        let mut child = Command::new("openssl")
+            .args(["pkey", "-pubin", "-inform", "DER", "-noout"])
+            .args(["-provider", &oqs_provider, "-provider", "default"])
+            .stdin(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child.stdin.take().unwrap().write_all(der).unwrap();
+        child.wait().unwrap().success()
+    }
+
+    #[test]
+    fn der_roundtrip() {
+        load_providers();
+        for group in [KxGroup::MLKEM512, KxGroup::MLKEM768, KxGroup::MLKEM1024] {
+            let kx = group.start().unwrap();
+            let der = PubKey::new(kx.pub_key(), group).to_der().unwrap();
+            assert!(openssl_pkey_parses(&der), "{}", group.algorithm());
+            assert_eq!(PubKey::from_der(&der, &group).unwrap(), kx.pub_key());
+        }
+    }
+
+    #[test]
+    fn der_errors() {
+        load_providers();
+        let kx = KxGroup::MLKEM768.start().unwrap();
+        let der = PubKey::new(kx.pub_key(), KxGroup::MLKEM768)
+            .to_der()
+            .unwrap();
+        assert!(matches!(
+            PubKey::from_der(&der, &KxGroup::MLKEM1024),
+            Err(DerError::WrongAlgorithm { expected }) if expected == "mlkem1024"
+        ));
+        assert!(matches!(
+            PubKey::from_der(&der[1..], &KxGroup::MLKEM768),
+            Err(DerError::Decode(_))
+        ));
+        assert!(matches!(
+            PubKey::new(&[0; 10], KxGroup::MLKEM768).to_der(),
+            Err(DerError::Encode(_))
+        ));
+    }
+}
@@ -0,0 +1,173 @@
+//! Ordered collections of key exchange groups.
+use crate::all_kx_groups;
+use crate::kem::{named_group_is_post_quantum, security_bits};
+use rustls::crypto::SupportedKxGroup;
+use rustls::NamedGroup;
+use std::fmt;
+
+/// An ordered set of key exchange groups, for building a [`CryptoProvider`](rustls::crypto::CryptoProvider)'s
+/// `kx_groups`.
+///
+/// Groups are identified by their [`NamedGroup`], and each appears at most once. The default set is
+/// [`all_kx_groups`].
+///
+/// ```
+/// use rustls::NamedGroup;
+/// use rustls_liboqs::KxGroupSet;
+///
+/// let mut groups = KxGroupSet::default();
+/// groups
+///     .filter_by_security_level(192)
+///     .remove(NamedGroup::MLKEM1024)
+///     .add(rustls::crypto::aws_lc_rs::kx_group::X25519);
+/// assert!(groups.contains(NamedGroup::X25519MLKEM768));
+/// assert!(!groups.contains(NamedGroup::MLKEM1024));
+/// ```
+#[derive(Clone)]
+pub struct KxGroupSet(Vec<&'static dyn SupportedKxGroup>);
+
+impl KxGroupSet {
+    /// Appends a group, unless a group with the same [`NamedGroup`] is already in the set.
+    pub fn add(&mut self, group: &'static dyn SupportedKxGroup) -> &mut Self {
+        if !self.contains(group.name()) {
+            self.0.push(group);
+        }
+        self
+    }
+
+    /// Removes the group with the given name, if any.
+    pub fn remove(&mut self, group: NamedGroup) -> &mut Self {
+        self.0.retain(|g| g.name() != group);
+        self
+    }
+
+    /// Returns whether the set has a group with the given name.
+    pub fn contains(&self, group: NamedGroup) -> bool {
+        self.0.iter().any(|g| g.name() == group)
+    }
+
+    /// Moves the post-quantum and hybrid groups before the classical groups, otherwise keeping their order.
+    pub fn prioritize_pq(&mut self) -> &mut Self {
+        self.0
+            .sort_by_key(|g| !named_group_is_post_quantum(g.name()));
+        self
+    }
+
+    /// Keeps only the groups with at least `min_bits` of post-quantum security.
+    ///
+    /// See [`KxGroup::security_bits`](crate::KxGroup::security_bits). Classical groups have no post-quantum
+    /// security, so are removed by any non-zero `min_bits`.
+    pub fn filter_by_security_level(&mut self, min_bits: u32) -> &mut Self {
+        self.0.retain(|g| security_bits(g.name()) >= min_bits);
+        self
+    }
+
+    /// Returns the groups in order of preference.
+    pub fn as_slice(&self) -> &[&'static dyn SupportedKxGroup] {
+        &self.0
+    }
+}
+
+impl Default for KxGroupSet {
+    fn default() -> Self {
+        Self(all_kx_groups().to_vec())
+    }
+}
+
+impl From<Vec<&'static dyn SupportedKxGroup>> for KxGroupSet {
+    /// Creates a set from the groups, keeping the first of any with the same [`NamedGroup`].
+    fn from(groups: Vec<&'static dyn SupportedKxGroup>) -> Self {
+        let mut set = Self(Vec::with_capacity(groups.len()));
+        for group in groups {
+            set.add(group);
+        }
+        set
+    }
+}
+
+impl From<KxGroupSet> for Vec<&'static dyn SupportedKxGroup> {
+    fn from(set: KxGroupSet) -> Self {
+        set.0
+    }
+}
+
+impl fmt::Debug for KxGroupSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.0.iter().map(|g| g.name()))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::KxGroupSet;
+    use crate::{all_kx_groups, MLKEM1024, MLKEM512, MLKEM768, X25519MLKEM768};
+    use rustls::crypto::aws_lc_rs::kx_group::{SECP256R1, X25519};
+    use rustls::NamedGroup;
+
+    fn names(set: &KxGroupSet) -> Vec<NamedGroup> {
+        set.as_slice().iter().map(|g| g.name()).collect()
+    }
+
+    #[test]
+    fn default_is_all_groups() {
+        let set = KxGroupSet::default();
+        assert_eq!(set.as_slice().len(), all_kx_groups().len());
+        for group in all_kx_groups() {
+            assert!(set.contains(group.name()));
+        }
+    }
+
+    #[test]
+    fn add_and_remove() {
+        let mut set = KxGroupSet::from(vec![MLKEM768, MLKEM768]);
+        assert_eq!(names(&set), [NamedGroup::MLKEM768]);
+
+        set.add(X25519).add(MLKEM768).add(MLKEM1024);
+        assert_eq!(
+            names(&set),
+            [
+                NamedGroup::MLKEM768,
+                NamedGroup::X25519,
+                NamedGroup::MLKEM1024
+            ]
+        );
+
+        set.remove(NamedGroup::MLKEM768)
+            .remove(NamedGroup::secp256r1);
+        assert!(!set.contains(NamedGroup::MLKEM768));
+        assert_eq!(names(&set), [NamedGroup::X25519, NamedGroup::MLKEM1024]);
+    }
+
+    #[test]
+    fn prioritize_pq() {
+        let mut set = KxGroupSet::from(vec![X25519, MLKEM512, SECP256R1, X25519MLKEM768]);
+        set.prioritize_pq();
+        assert_eq!(
+            names(&set),
+            [
+                NamedGroup::MLKEM512,
+                NamedGroup::X25519MLKEM768,
+                NamedGroup::X25519,
+                NamedGroup::secp256r1
+            ]
+        );
+
+        // The crate's own groups are all post-quantum, so their order is unchanged
+        let mut set = KxGroupSet::default();
+        set.prioritize_pq();
+        assert_eq!(names(&set), names(&KxGroupSet::default()));
+    }
+
+    #[test]
+    fn filter_by_security_level() {
+        let mut set = KxGroupSet::from(vec![X25519, MLKEM512, MLKEM768, MLKEM1024]);
+        set.filter_by_security_level(192);
+        assert_eq!(names(&set), [NamedGroup::MLKEM768, NamedGroup::MLKEM1024]);
+
+        let mut set = KxGroupSet::from(vec![X25519, MLKEM512]);
+        set.filter_by_security_level(0);
+        assert_eq!(set.as_slice().len(), 2);
+    }
+}
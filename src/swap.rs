@@ -0,0 +1,174 @@
+//! Reordering of hybrid key exchange components.
+use crate::kem::{KeyExchange, KxGroup};
+use rustls::crypto::{ActiveKeyExchange, CompletedKeyExchange, SharedSecret, SupportedKxGroup};
+use rustls::{Error, NamedGroup, ProtocolVersion};
+
+/// A hybrid key exchange group that swaps the order of the classical and post-quantum components.
+///
+/// The [X25519MLKEM768] draft places the ML-KEM component first in public keys, ciphertexts and shared secrets,
+/// unlike the other hybrid groups which place the classical component first. oqs-provider versions before 0.7.0
+/// (built against liboqs before 0.12) encode X25519MLKEM768 with the classical component first, so on the wire
+/// the components must be swapped to interoperate with other implementations.
+///
+/// The wrapped group uses the classical-first encoding, and this group converts to the post-quantum-first
+/// encoding. `classical_len` is the length of the classical public key, ciphertext and shared secret, which are
+/// all the same for X25519 (32 bytes) and X448 (56 bytes).
+///
+/// [X25519MLKEM768]: <https://datatracker.ietf.org/doc/draft-kwiatkowski-tls-ecdhe-mlkem/>
+#[derive(Debug, Copy, Clone)]
+pub struct ReversingKeyExchange {
+    /// The hybrid group using the classical-first encoding.
+    pub inner: KxGroup,
+    /// The length of the classical component.
+    pub classical_len: usize,
+}
+
+impl ReversingKeyExchange {
+    /// Moves the classical component from the start to the end.
+    fn reverse(&self, bytes: &[u8]) -> Result<Vec<u8>, Error> {
+        let (classical, pq) = bytes
+            .split_at_checked(self.classical_len)
+            .ok_or_else(|| self.too_short(bytes))?;
+        Ok([pq, classical].concat())
+    }
+
+    /// Moves the classical component from the end to the start.
+    fn unreverse(&self, bytes: &[u8]) -> Result<Vec<u8>, Error> {
+        let (pq, classical) = bytes
+            .len()
+            .checked_sub(self.classical_len)
+            .map(|pq_len| bytes.split_at(pq_len))
+            .ok_or_else(|| self.too_short(bytes))?;
+        Ok([classical, pq].concat())
+    }
+
+    fn too_short(&self, bytes: &[u8]) -> Error {
+        Error::General(format!(
+            "{} component of {} bytes is shorter than the classical component",
+            self.inner,
+            bytes.len()
+        ))
+    }
+}
+
+impl SupportedKxGroup for ReversingKeyExchange {
+    fn start(&self) -> Result<Box<dyn ActiveKeyExchange>, Error> {
+        let inner = self.inner.start_key_exchange()?;
+        let pub_key = self.reverse(inner.pub_key())?;
+        Ok(Box::new(ReversedKeyExchange {
+            inner,
+            group: *self,
+            pub_key,
+        }))
+    }
+
+    fn name(&self) -> NamedGroup {
+        self.inner.name()
+    }
+
+    fn usable_for_version(&self, version: ProtocolVersion) -> bool {
+        version == ProtocolVersion::TLSv1_3
+    }
+
+    fn ffdhe_group(&self) -> Option<rustls::ffdhe_groups::FfdheGroup<'static>> {
+        None
+    }
+
+    fn start_and_complete(&self, peer_pub_key: &[u8]) -> Result<CompletedKeyExchange, Error> {
+        let completed = self
+            .inner
+            .start_and_complete(&self.unreverse(peer_pub_key)?)?;
+        Ok(CompletedKeyExchange {
+            group: completed.group,
+            pub_key: self.reverse(&completed.pub_key)?,
+            secret: SharedSecret::from(self.reverse(completed.secret.secret_bytes())?),
+        })
+    }
+}
+
+/// An in-progress key exchange for a [`ReversingKeyExchange`].
+struct ReversedKeyExchange {
+    inner: Box<KeyExchange>,
+    group: ReversingKeyExchange,
+    pub_key: Vec<u8>,
+}
+
+impl ActiveKeyExchange for ReversedKeyExchange {
+    fn complete(self: Box<Self>, peer_pub_key: &[u8]) -> Result<SharedSecret, Error> {
+        let ciphertext = self.group.unreverse(peer_pub_key)?;
+        let secret = self.inner.complete(&ciphertext)?;
+        self.group
+            .reverse(secret.secret_bytes())
+            .map(SharedSecret::from)
+    }
+
+    fn pub_key(&self) -> &[u8] {
+        &self.pub_key
+    }
+
+    fn group(&self) -> NamedGroup {
+        self.group.name()
+    }
+
+    fn hybrid_component(&self) -> Option<(NamedGroup, &[u8])> {
+        self.inner.hybrid_component()
+    }
+
+    fn complete_hybrid_component(
+        self: Box<Self>,
+        peer_pub_key: &[u8],
+    ) -> Result<SharedSecret, Error> {
+        self.inner.complete_hybrid_component(peer_pub_key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rustls::crypto::SupportedKxGroup;
+
+    use super::ReversingKeyExchange;
+    use crate::tests::{load_providers, roundtrip};
+    use crate::KxGroup;
+
+    const REVERSED: ReversingKeyExchange = ReversingKeyExchange {
+        inner: KxGroup::X25519MLKEM768,
+        classical_len: 32,
+    };
+
+    #[test]
+    fn swap() {
+        let group = ReversingKeyExchange {
+            inner: KxGroup::X25519MLKEM768,
+            classical_len: 2,
+        };
+        assert_eq!(group.reverse(&[1, 2, 3, 4, 5]).unwrap(), [3, 4, 5, 1, 2]);
+        assert_eq!(group.unreverse(&[3, 4, 5, 1, 2]).unwrap(), [1, 2, 3, 4, 5]);
+        assert!(group.reverse(&[1]).is_err());
+        assert!(group.unreverse(&[1]).is_err());
+    }
+
+    #[test]
+    fn reversed_roundtrip() {
+        roundtrip(&REVERSED, &REVERSED);
+    }
+
+    #[test]
+    fn same_secret_as_unswapped() {
+        load_providers();
+
+        // Encapsulate with the unswapped group to a reversed key exchange
+        let kx = REVERSED.start().unwrap();
+        let completed = REVERSED
+            .inner
+            .start_and_complete(&REVERSED.unreverse(kx.pub_key()).unwrap())
+            .unwrap();
+        let secret = kx
+            .complete(&REVERSED.reverse(&completed.pub_key).unwrap())
+            .unwrap();
+
+        assert_eq!(
+            secret.secret_bytes(),
+            REVERSED.reverse(completed.secret.secret_bytes()).unwrap()
+        );
+    }
+}
@@ -0,0 +1,184 @@
+//! Helpers for testing key exchange groups, e.g custom groups created with
+//! [`KxGroup::new`](crate::KxGroup::new) or
+//! [`KxGroup::with_custom_algorithm_name`](crate::KxGroup::with_custom_algorithm_name).
+//!
+//! These are the checks this crate runs against its own groups. They panic on failure, so are intended to be
+//! called from tests. The OpenSSL providers the groups use must already be loaded:
+//!
+//! ```no_run
+//! use rustls_liboqs::{test_utils, KxGroup, OqsProviderGuard};
+//!
+//! let _providers = OqsProviderGuard::load().unwrap();
+//! let group = KxGroup::MLKEM768.with_custom_algorithm_name(b"MLKEM768\0");
+//! test_utils::assert_roundtrip(&group);
+//! test_utils::roundtrip(&group, &KxGroup::MLKEM768);
+//! ```
+//!
+//! This module requires the `test-utils` feature.
+use rustls::crypto::{ActiveKeyExchange, SharedSecret, SupportedKxGroup};
+use rustls::{Error, NamedGroup};
+
+/// Checks that `ours` and `theirs` agree on a shared secret, with each side as the client and as the server.
+///
+/// The groups must be the same key exchange, e.g this crate's group and another implementation of it.
+pub fn roundtrip(ours: &dyn SupportedKxGroup, theirs: &dyn SupportedKxGroup) {
+    let our_kx = ours.start().unwrap();
+    let their_kx = theirs.start().unwrap();
+
+    let our_completed = ours.start_and_complete(their_kx.pub_key()).unwrap();
+    let their_secret = their_kx.complete(&our_completed.pub_key).unwrap();
+
+    assert_eq!(
+        our_completed.secret.secret_bytes(),
+        their_secret.secret_bytes()
+    );
+    check_secret(ours, our_completed.secret.secret_bytes());
+
+    let their_completed = theirs.start_and_complete(our_kx.pub_key()).unwrap();
+    let our_secret = our_kx.complete(&their_completed.pub_key).unwrap();
+
+    assert_eq!(
+        our_secret.secret_bytes(),
+        their_completed.secret.secret_bytes()
+    );
+    check_secret(ours, our_secret.secret_bytes());
+}
+
+/// Checks that the classical component of the hybrid group `ours` agrees on a shared secret with the classical
+/// group `theirs`, e.g X25519MLKEM768 with X25519.
+///
+/// See [`ActiveKeyExchange::hybrid_component`].
+pub fn roundtrip_classical(ours: &dyn SupportedKxGroup, theirs: &dyn SupportedKxGroup) {
+    let our_kx = ours.start().unwrap();
+    let their_kx = theirs.start().unwrap();
+
+    let (_group, our_key) = our_kx.hybrid_component().unwrap();
+    let our_key = our_key.to_vec();
+
+    let peer_pub_key = their_kx.pub_key();
+    let our_secret = our_kx.complete_hybrid_component(peer_pub_key).unwrap();
+    let their_secret = their_kx.complete(&our_key).unwrap();
+
+    assert_eq!(our_secret.secret_bytes(), their_secret.secret_bytes());
+    assert!(our_secret.secret_bytes().iter().any(|&b| b != 0));
+}
+
+/// Checks that `group` agrees on a shared secret with itself.
+pub fn assert_roundtrip(group: &dyn SupportedKxGroup) {
+    roundtrip(group, group);
+}
+
+/// A key exchange group that doesn't do any cryptography, for testing code that uses [`SupportedKxGroup`]s without
+/// loading OpenSSL providers.
+///
+/// Every key exchange has the public key [`MockKxGroup::PUB_KEY`], and completes with `shared_secret` whatever the
+/// peer's public key, so the two sides of a handshake both using the group agree.
+///
+/// ```
+/// use rustls::crypto::SupportedKxGroup;
+/// use rustls::NamedGroup;
+/// use rustls_liboqs::test_utils::MockKxGroup;
+///
+/// let group = MockKxGroup {
+///     named_group: NamedGroup::MLKEM768,
+///     shared_secret: vec![1; 32],
+/// };
+/// let kx = group.start().unwrap();
+/// assert_eq!(kx.pub_key(), MockKxGroup::PUB_KEY);
+/// assert_eq!(kx.complete(b"anything").unwrap().secret_bytes(), [1; 32]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct MockKxGroup {
+    /// The group's name.
+    pub named_group: NamedGroup,
+    /// The shared secret every key exchange completes with.
+    pub shared_secret: Vec<u8>,
+}
+
+impl MockKxGroup {
+    /// The public key of every key exchange.
+    pub const PUB_KEY: &'static [u8] = b"rustls-liboqs mock public key";
+}
+
+impl SupportedKxGroup for MockKxGroup {
+    fn start(&self) -> Result<Box<dyn ActiveKeyExchange>, Error> {
+        Ok(Box::new(self.clone()))
+    }
+
+    fn name(&self) -> NamedGroup {
+        self.named_group
+    }
+}
+
+impl ActiveKeyExchange for MockKxGroup {
+    fn complete(self: Box<Self>, _peer_pub_key: &[u8]) -> Result<SharedSecret, Error> {
+        Ok(SharedSecret::from(self.shared_secret.as_slice()))
+    }
+
+    fn pub_key(&self) -> &[u8] {
+        Self::PUB_KEY
+    }
+
+    fn group(&self) -> NamedGroup {
+        self.named_group
+    }
+}
+
+/// A key exchange group that always fails, for testing how code handles a group that's configured but unavailable
+/// at runtime, e.g because oqsprovider isn't loaded.
+///
+/// [`SupportedKxGroup::start`] and [`SupportedKxGroup::start_and_complete`] return
+/// `Err(Error::General("disabled"))`.
+#[derive(Debug, Copy, Clone)]
+pub struct DisabledKxGroup(pub NamedGroup);
+
+impl SupportedKxGroup for DisabledKxGroup {
+    fn start(&self) -> Result<Box<dyn ActiveKeyExchange>, Error> {
+        Err(Error::General("disabled".into()))
+    }
+
+    fn name(&self) -> NamedGroup {
+        self.0
+    }
+}
+
+/// Checks a shared secret isn't all zeros.
+///
+/// The length isn't checked, as a custom group may reuse the [`NamedGroup`] of one of this crate's groups with a
+/// different algorithm.
+fn check_secret(group: &dyn SupportedKxGroup, secret: &[u8]) {
+    assert!(secret.iter().any(|&b| b != 0), "{:?}", group.name());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{assert_roundtrip, DisabledKxGroup, MockKxGroup};
+    use rustls::crypto::SupportedKxGroup;
+    use rustls::{Error, NamedGroup};
+
+    #[test]
+    fn mock_kx_group() {
+        let group = MockKxGroup {
+            named_group: NamedGroup::MLKEM768,
+            shared_secret: vec![7; 32],
+        };
+        assert_eq!(group.name(), NamedGroup::MLKEM768);
+        assert_roundtrip(&group);
+
+        let completed = group.start_and_complete(b"peer").unwrap();
+        assert_eq!(completed.group, NamedGroup::MLKEM768);
+        assert_eq!(completed.pub_key, MockKxGroup::PUB_KEY);
+        assert_eq!(completed.secret.secret_bytes(), [7; 32]);
+    }
+
+    #[test]
+    fn disabled_kx_group() {
+        let group = DisabledKxGroup(NamedGroup::X25519MLKEM768);
+        assert_eq!(group.name(), NamedGroup::X25519MLKEM768);
+        assert!(matches!(group.start(), Err(Error::General(message)) if message == "disabled"));
+        assert!(matches!(
+            group.start_and_complete(MockKxGroup::PUB_KEY),
+            Err(Error::General(message)) if message == "disabled"
+        ));
+    }
+}
@@ -0,0 +1,209 @@
+//! Validation of rustls configurations for post-quantum key exchange.
+use rustls::crypto::CryptoProvider;
+use rustls::{ClientConfig, NamedGroup, ProtocolVersion, ServerConfig, SupportedProtocolVersion};
+
+use crate::kem::named_group_is_post_quantum;
+
+/// A potential problem with the post-quantum key exchange configuration of a rustls config.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ConfigWarning {
+    /// None of the configured key exchange groups are post-quantum.
+    NoPostQuantumGroups,
+    /// The key exchange group can't be negotiated with TLS 1.3, either because the group doesn't
+    /// support TLS 1.3, because no TLS 1.3 cipher suites are configured, or because TLS 1.3 isn't
+    /// an enabled protocol version.
+    TlsVersionMismatch(NamedGroup),
+    /// A classical key exchange group is configured alongside post-quantum groups, so peers that
+    /// don't support post-quantum key exchange will fall back to it.
+    ClassicalOnlyFallbackEnabled(NamedGroup),
+}
+
+/// Validates rustls configurations for post-quantum key exchange.
+///
+/// rustls doesn't expose the protocol versions a config enables, so they must be passed alongside
+/// the config, i.e the versions given to `with_protocol_versions`, or [`rustls::DEFAULT_VERSIONS`]
+/// if the config was built with `with_safe_default_protocol_versions`.
+#[derive(Debug)]
+pub struct PqConfigValidator;
+
+impl PqConfigValidator {
+    /// Returns any warnings about the key exchange configuration of a client config that enables
+    /// `versions`.
+    pub fn validate_client(
+        config: &ClientConfig,
+        versions: &[&SupportedProtocolVersion],
+    ) -> Vec<ConfigWarning> {
+        Self::validate_provider(config.crypto_provider(), versions)
+    }
+
+    /// Returns any warnings about the key exchange configuration of a server config that enables
+    /// `versions`.
+    pub fn validate_server(
+        config: &ServerConfig,
+        versions: &[&SupportedProtocolVersion],
+    ) -> Vec<ConfigWarning> {
+        Self::validate_provider(config.crypto_provider(), versions)
+    }
+
+    fn validate_provider(
+        provider: &CryptoProvider,
+        versions: &[&SupportedProtocolVersion],
+    ) -> Vec<ConfigWarning> {
+        let mut warnings = Vec::new();
+
+        let tls13_enabled = versions
+            .iter()
+            .any(|version| version.version == ProtocolVersion::TLSv1_3);
+        let tls13_negotiable = tls13_enabled
+            && provider
+                .cipher_suites
+                .iter()
+                .any(|suite| suite.version().version == ProtocolVersion::TLSv1_3);
+        let has_pq_groups = provider
+            .kx_groups
+            .iter()
+            .any(|group| named_group_is_post_quantum(group.name()));
+
+        if !has_pq_groups {
+            warnings.push(ConfigWarning::NoPostQuantumGroups);
+        }
+
+        for group in &provider.kx_groups {
+            let name = group.name();
+            if named_group_is_post_quantum(name) {
+                if !tls13_negotiable || !group.usable_for_version(ProtocolVersion::TLSv1_3) {
+                    warnings.push(ConfigWarning::TlsVersionMismatch(name));
+                }
+            } else if has_pq_groups {
+                warnings.push(ConfigWarning::ClassicalOnlyFallbackEnabled(name));
+            }
+        }
+
+        warnings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use rustls::crypto::{ActiveKeyExchange, CompletedKeyExchange, SupportedKxGroup};
+    use rustls::server::ResolvesServerCertUsingSni;
+    use rustls::version::TLS12;
+    use rustls::{
+        ClientConfig, NamedGroup, ProtocolVersion, RootCertStore, ServerConfig, ALL_VERSIONS,
+    };
+    use rustls_openssl::cipher_suite::{
+        TLS13_AES_128_GCM_SHA256, TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256,
+    };
+    use rustls_openssl::custom_provider;
+    use rustls_openssl::kx_group::X25519;
+
+    use super::{ConfigWarning, PqConfigValidator};
+    use crate::{MLKEM768, X25519MLKEM768};
+
+    /// A post-quantum group that claims not to support TLS 1.3.
+    #[derive(Debug)]
+    struct Tls12OnlyGroup;
+
+    impl SupportedKxGroup for Tls12OnlyGroup {
+        fn start(&self) -> Result<Box<dyn ActiveKeyExchange>, rustls::Error> {
+            MLKEM768.start()
+        }
+
+        fn start_and_complete(
+            &self,
+            peer_pub_key: &[u8],
+        ) -> Result<CompletedKeyExchange, rustls::Error> {
+            MLKEM768.start_and_complete(peer_pub_key)
+        }
+
+        fn name(&self) -> NamedGroup {
+            MLKEM768.name()
+        }
+
+        fn usable_for_version(&self, version: ProtocolVersion) -> bool {
+            version == ProtocolVersion::TLSv1_2
+        }
+    }
+
+    fn client_config(
+        kx_groups: Vec<&'static dyn SupportedKxGroup>,
+        versions: &[&'static rustls::SupportedProtocolVersion],
+    ) -> ClientConfig {
+        let provider = custom_provider(
+            vec![
+                TLS13_AES_128_GCM_SHA256,
+                TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256,
+            ],
+            kx_groups,
+        );
+        ClientConfig::builder_with_provider(Arc::new(provider))
+            .with_protocol_versions(versions)
+            .unwrap()
+            .with_root_certificates(RootCertStore::empty())
+            .with_no_client_auth()
+    }
+
+    #[test]
+    fn valid_config() {
+        let config = client_config(vec![X25519MLKEM768, MLKEM768], ALL_VERSIONS);
+        assert_eq!(
+            PqConfigValidator::validate_client(&config, ALL_VERSIONS),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn no_post_quantum_groups() {
+        let config = client_config(vec![X25519], ALL_VERSIONS);
+        assert_eq!(
+            PqConfigValidator::validate_client(&config, ALL_VERSIONS),
+            vec![ConfigWarning::NoPostQuantumGroups]
+        );
+    }
+
+    #[test]
+    fn tls_version_mismatch() {
+        let config = client_config(vec![&Tls12OnlyGroup], ALL_VERSIONS);
+        assert_eq!(
+            PqConfigValidator::validate_client(&config, ALL_VERSIONS),
+            vec![ConfigWarning::TlsVersionMismatch(NamedGroup::MLKEM768)]
+        );
+
+        let provider = custom_provider(vec![TLS_ECDHE_RSA_WITH_AES_128_GCM_SHA256], vec![MLKEM768]);
+        let config = ServerConfig::builder_with_provider(Arc::new(provider))
+            .with_protocol_versions(&[&TLS12])
+            .unwrap()
+            .with_no_client_auth()
+            .with_cert_resolver(Arc::new(ResolvesServerCertUsingSni::new()));
+        assert_eq!(
+            PqConfigValidator::validate_server(&config, &[&TLS12]),
+            vec![ConfigWarning::TlsVersionMismatch(NamedGroup::MLKEM768)]
+        );
+    }
+
+    #[test]
+    fn tls12_forced() {
+        // The provider has TLS 1.3 suites, but the config only enables TLS 1.2
+        let config = client_config(vec![X25519MLKEM768], &[&TLS12]);
+        assert_eq!(
+            PqConfigValidator::validate_client(&config, &[&TLS12]),
+            vec![ConfigWarning::TlsVersionMismatch(
+                NamedGroup::X25519MLKEM768
+            )]
+        );
+    }
+
+    #[test]
+    fn classical_fallback() {
+        let config = client_config(vec![X25519MLKEM768, X25519], ALL_VERSIONS);
+        assert_eq!(
+            PqConfigValidator::validate_client(&config, ALL_VERSIONS),
+            vec![ConfigWarning::ClassicalOnlyFallbackEnabled(
+                NamedGroup::X25519
+            )]
+        );
+    }
+}
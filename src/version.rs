@@ -0,0 +1,114 @@
+//! Parsing and comparison of oqsprovider versions.
+//!
+//! This module is also included by the build script, so it must not depend on the rest of the crate.
+use std::fmt;
+use std::str::FromStr;
+
+/// An oqsprovider version, e.g `0.7.0`.
+///
+/// Versions are ordered by major, minor and then patch version, so can be compared against the first version
+/// with a fix or feature:
+///
+/// ```
+/// use rustls_liboqs::OqsProviderVersion;
+///
+/// let version: OqsProviderVersion = "0.6.1".parse().unwrap();
+/// assert!(version < OqsProviderVersion::new(0, 7, 0));
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct OqsProviderVersion {
+    /// The major version.
+    pub major: u32,
+    /// The minor version.
+    pub minor: u32,
+    /// The patch version.
+    pub patch: u32,
+}
+
+impl OqsProviderVersion {
+    /// Creates a version from its components.
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+}
+
+impl FromStr for OqsProviderVersion {
+    type Err = ParseVersionError;
+
+    /// Parses a version such as `0.7.0`, `0.12.1.0` or `0.8.0-dev`.
+    ///
+    /// The major and minor versions are required. A missing patch version is 0, and any suffix after the patch
+    /// version's digits, or any further components, are ignored.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let err = || ParseVersionError(s.to_owned());
+        let mut parts = s.trim().split('.');
+        let major = parts.next().and_then(|p| p.parse().ok()).ok_or_else(err)?;
+        let minor = parts.next().and_then(|p| p.parse().ok()).ok_or_else(err)?;
+        let patch = match parts.next() {
+            None => 0,
+            Some(part) => {
+                let digits = part
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(part.len());
+                part[..digits].parse().map_err(|_| err())?
+            }
+        };
+        Ok(Self::new(major, minor, patch))
+    }
+}
+
+impl fmt::Display for OqsProviderVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// An error returned when parsing an [`OqsProviderVersion`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseVersionError(String);
+
+impl fmt::Display for ParseVersionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid oqsprovider version {:?}", self.0)
+    }
+}
+
+impl std::error::Error for ParseVersionError {}
+
+#[cfg(test)]
+mod tests {
+    use super::OqsProviderVersion;
+
+    #[test]
+    fn parse() {
+        for (version, expected) in [
+            ("0.7.0", OqsProviderVersion::new(0, 7, 0)),
+            (" 0.12.1\n", OqsProviderVersion::new(0, 12, 1)),
+            ("0.12.1.0", OqsProviderVersion::new(0, 12, 1)),
+            ("0.8.0-dev", OqsProviderVersion::new(0, 8, 0)),
+            ("1.2", OqsProviderVersion::new(1, 2, 0)),
+        ] {
+            assert_eq!(version.parse(), Ok(expected), "{version}");
+        }
+
+        for version in ["", "1", "a.b.c", "0.x", "0.7.x"] {
+            let err = version.parse::<OqsProviderVersion>().unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                format!("invalid oqsprovider version {version:?}")
+            );
+        }
+    }
+
+    #[test]
+    fn ordering() {
+        assert!(OqsProviderVersion::new(0, 7, 0) > OqsProviderVersion::new(0, 6, 9));
+        assert!(OqsProviderVersion::new(0, 10, 0) > OqsProviderVersion::new(0, 9, 0));
+        assert!(OqsProviderVersion::new(1, 0, 0) > OqsProviderVersion::new(0, 12, 1));
+        assert_eq!(OqsProviderVersion::new(0, 7, 0).to_string(), "0.7.0");
+    }
+}
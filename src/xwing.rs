@@ -0,0 +1,218 @@
+//! The [X-Wing] hybrid key encapsulation mechanism.
+//!
+//! X-Wing combines ML-KEM-768 and X25519, but unlike [`crate::X25519MLKEM768`] the shared secret isn't a
+//! concatenation of the component secrets. Instead both secrets, the X25519 ciphertext and the X25519 public key
+//! are hashed together with SHA3-256, so the group can't be implemented by [`crate::KxGroup`].
+//!
+//! [X-Wing]: <https://datatracker.ietf.org/doc/draft-connolly-cfrg-xwing-kem/>
+use crate::error::KemError;
+use crate::kem::{KeyExchange, KxGroup};
+use openssl::derive::Deriver;
+use openssl::error::ErrorStack;
+use openssl::hash::{hash, MessageDigest};
+use openssl::pkey::{Id, PKey, Private};
+use rustls::crypto::{ActiveKeyExchange, CompletedKeyExchange, SharedSecret, SupportedKxGroup};
+use rustls::{Error, NamedGroup, ProtocolVersion};
+
+/// This is the [X-Wing] key exchange.
+///
+/// [X-Wing]: <https://datatracker.ietf.org/doc/draft-connolly-cfrg-xwing-kem/>
+pub const XWING: &dyn SupportedKxGroup = &XWingKxGroup;
+
+/// The provisional codepoint for X-Wing from the X-Wing draft.
+pub(crate) const X_WING: NamedGroup = NamedGroup::Unknown(0x647a);
+
+/// The algorithm name used in errors.
+const ALGORITHM: &str = "X-Wing";
+
+/// The domain separation label, `\.//^\`.
+const X_WING_LABEL: &[u8] = br"\.//^\";
+
+const MLKEM768_PUB_KEY_LEN: usize = 1184;
+const MLKEM768_CIPHERTEXT_LEN: usize = 1088;
+const X25519_LEN: usize = 32;
+
+/// The X-Wing key exchange group.
+#[derive(Debug, Copy, Clone)]
+pub struct XWingKxGroup;
+
+impl SupportedKxGroup for XWingKxGroup {
+    fn start(&self) -> Result<Box<dyn ActiveKeyExchange>, Error> {
+        XWingKeyExchange::generate()
+            .map(|kx| Box::new(kx) as Box<dyn ActiveKeyExchange>)
+            .map_err(|source| {
+                KemError::Keygen {
+                    algorithm: ALGORITHM.to_string(),
+                    source,
+                }
+                .into()
+            })
+    }
+
+    fn name(&self) -> NamedGroup {
+        X_WING
+    }
+
+    fn usable_for_version(&self, version: ProtocolVersion) -> bool {
+        version == ProtocolVersion::TLSv1_3
+    }
+
+    fn ffdhe_group(&self) -> Option<rustls::ffdhe_groups::FfdheGroup<'static>> {
+        None
+    }
+
+    fn start_and_complete(&self, peer_pub_key: &[u8]) -> Result<CompletedKeyExchange, Error> {
+        if peer_pub_key.len() != MLKEM768_PUB_KEY_LEN + X25519_LEN {
+            return Err(KemError::InvalidPublicKey {
+                algorithm: ALGORITHM.to_string(),
+                expected_len: Some(MLKEM768_PUB_KEY_LEN + X25519_LEN),
+                got_len: peer_pub_key.len(),
+            }
+            .into());
+        }
+        let (pk_m, pk_x) = peer_pub_key.split_at(MLKEM768_PUB_KEY_LEN);
+
+        let mlkem = KxGroup::MLKEM768.start_and_complete(pk_m)?;
+
+        PKey::generate_x25519()
+            .and_then(|ephemeral| {
+                let ct_x = ephemeral.raw_public_key()?;
+                let ss_x = x25519(&ephemeral, pk_x)?;
+                let secret = combiner(mlkem.secret.secret_bytes(), &ss_x, &ct_x, pk_x)?;
+
+                let mut ciphertext = mlkem.pub_key;
+                ciphertext.extend_from_slice(&ct_x);
+                Ok(CompletedKeyExchange {
+                    group: X_WING,
+                    pub_key: ciphertext,
+                    secret,
+                })
+            })
+            .map_err(|source| {
+                KemError::Encapsulate {
+                    algorithm: ALGORITHM.to_string(),
+                    source,
+                }
+                .into()
+            })
+    }
+}
+
+/// An in-progress X-Wing key exchange.
+struct XWingKeyExchange {
+    mlkem: KeyExchange,
+    x25519: PKey<Private>,
+    pub_key: Vec<u8>,
+}
+
+impl XWingKeyExchange {
+    fn generate() -> Result<Self, ErrorStack> {
+        let mlkem = KeyExchange::generate(KxGroup::MLKEM768)?;
+        let x25519 = PKey::generate_x25519()?;
+
+        let mut pub_key = mlkem.pub_key().to_vec();
+        pub_key.extend_from_slice(&x25519.raw_public_key()?);
+        Ok(Self {
+            mlkem,
+            x25519,
+            pub_key,
+        })
+    }
+}
+
+impl ActiveKeyExchange for XWingKeyExchange {
+    fn complete(self: Box<Self>, peer_pub_key: &[u8]) -> Result<SharedSecret, Error> {
+        if peer_pub_key.len() != MLKEM768_CIPHERTEXT_LEN + X25519_LEN {
+            return Err(KemError::InvalidCiphertext {
+                algorithm: ALGORITHM.to_string(),
+                expected_len: MLKEM768_CIPHERTEXT_LEN + X25519_LEN,
+                got_len: peer_pub_key.len(),
+            }
+            .into());
+        }
+        let (ct_m, ct_x) = peer_pub_key.split_at(MLKEM768_CIPHERTEXT_LEN);
+
+        let ss_m = self.mlkem.decapsulate(ct_m)?;
+        let pk_x = &self.pub_key[MLKEM768_PUB_KEY_LEN..];
+        x25519(&self.x25519, ct_x)
+            .and_then(|ss_x| combiner(ss_m.secret_bytes(), &ss_x, ct_x, pk_x))
+            .map_err(|source| {
+                KemError::Decapsulate {
+                    algorithm: ALGORITHM.to_string(),
+                    source,
+                }
+                .into()
+            })
+    }
+
+    fn pub_key(&self) -> &[u8] {
+        &self.pub_key
+    }
+
+    fn group(&self) -> NamedGroup {
+        X_WING
+    }
+}
+
+/// Performs an X25519 key agreement with the peer's raw public key.
+fn x25519(priv_key: &PKey<Private>, peer_pub_key: &[u8]) -> Result<Vec<u8>, ErrorStack> {
+    let peer_pub_key = PKey::public_key_from_raw_bytes(peer_pub_key, Id::X25519)?;
+    let mut deriver = Deriver::new(priv_key)?;
+    deriver.set_peer(&peer_pub_key)?;
+    deriver.derive_to_vec()
+}
+
+/// The X-Wing combiner, `SHA3-256(ss_M || ss_X || ct_X || pk_X || XWingLabel)`.
+fn combiner(
+    ss_m: &[u8],
+    ss_x: &[u8],
+    ct_x: &[u8],
+    pk_x: &[u8],
+) -> Result<SharedSecret, ErrorStack> {
+    let input = [ss_m, ss_x, ct_x, pk_x, X_WING_LABEL].concat();
+    let digest = hash(MessageDigest::sha3_256(), &input)?;
+    Ok(SharedSecret::from(&digest[..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use x_wing::{Decapsulate, Encapsulate, EncapsulationKey, Kem, KeyExport, XWingKem};
+
+    use super::XWING;
+    use crate::tests::{load_providers, roundtrip};
+
+    #[test]
+    fn xwing() {
+        roundtrip(XWING, XWING);
+    }
+
+    #[test]
+    fn xwing_reference_interop() {
+        load_providers();
+
+        // We decapsulate a ciphertext from the reference implementation
+        let kx = XWING.start().unwrap();
+        let ek = EncapsulationKey::try_from(kx.pub_key()).unwrap();
+        let (ct, expected) = ek.encapsulate();
+        let secret = kx.complete(&ct).unwrap();
+        assert_eq!(secret.secret_bytes(), expected.as_slice());
+
+        // The reference implementation decapsulates our ciphertext
+        let (dk, ek) = XWingKem::generate_keypair();
+        let completed = XWING.start_and_complete(&ek.to_bytes()).unwrap();
+        let ct = completed.pub_key.as_slice().try_into().unwrap();
+        let expected = dk.decapsulate(&ct);
+        assert_eq!(completed.secret.secret_bytes(), expected.as_slice());
+    }
+
+    #[test]
+    fn wrong_lengths() {
+        let err = XWING.start_and_complete(&[0; 1215]).err().unwrap();
+        assert!(err.to_string().contains("expected 1216 bytes, got 1215"));
+
+        load_providers();
+        let kx = XWING.start().unwrap();
+        let err = kx.complete(&[0; 1121]).err().unwrap();
+        assert!(err.to_string().contains("expected 1120 bytes, got 1121"));
+    }
+}
@@ -0,0 +1,33 @@
+//! Tests for the version parsing used by the build script.
+#[path = "../build/version.rs"]
+mod version;
+
+use rustls_liboqs::OqsProviderVersion;
+use version::parse_oqs_version_text;
+
+/// The build script parses versions with [`OqsProviderVersion`], by including its source.
+#[test]
+fn parse_versions() {
+    let parse = |version: &str| version.parse::<OqsProviderVersion>().ok();
+    assert_eq!(parse("0.12.0"), Some(OqsProviderVersion::new(0, 12, 0)));
+    assert_eq!(parse("0.12.1.0"), Some(OqsProviderVersion::new(0, 12, 1)));
+    assert_eq!(parse("1.0"), Some(OqsProviderVersion::new(1, 0, 0)));
+    assert_eq!(parse(""), None);
+    assert_eq!(parse("1"), None);
+    assert_eq!(parse("0.x.1"), None);
+}
+
+#[test]
+fn parse_oqsconfig_header() {
+    let header = "#define OQS_VERSION_MAJOR 0\n#define OQS_VERSION_TEXT \"0.12.0\"\n#define OQS_COMPILE_BUILD_TARGET \"x86_64-Windows\"\n";
+    assert_eq!(parse_oqs_version_text(header), Some("0.12.0"));
+    assert_eq!(
+        parse_oqs_version_text("#define OQS_VERSION_TEXTS \"1.0\""),
+        None
+    );
+    assert_eq!(
+        parse_oqs_version_text("#define OQS_VERSION_TEXT 0.12.0"),
+        None
+    );
+    assert_eq!(parse_oqs_version_text(""), None);
+}
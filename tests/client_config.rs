@@ -0,0 +1,106 @@
+//! End-to-end test of [`make_client_config`] against a local rustls server.
+#![cfg(feature = "rustls-openssl")]
+use openssl::asn1::Asn1Time;
+use openssl::ec::{EcGroup, EcKey};
+use openssl::hash::MessageDigest;
+use openssl::nid::Nid;
+use openssl::pkey::PKey;
+use openssl::x509::extension::SubjectAlternativeName;
+use openssl::x509::{X509NameBuilder, X509};
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
+use rustls::{ClientConnection, RootCertStore, ServerConfig, ServerConnection};
+use rustls_liboqs::{make_client_config, MLKEM768};
+use std::sync::Arc;
+
+/// Generates a self-signed ECDSA P-256 certificate for `localhost`.
+fn self_signed_cert() -> (CertificateDer<'static>, PrivateKeyDer<'static>) {
+    let key = PKey::from_ec_key(
+        EcKey::generate(&EcGroup::from_curve_name(Nid::X9_62_PRIME256V1).unwrap()).unwrap(),
+    )
+    .unwrap();
+
+    let mut name = X509NameBuilder::new().unwrap();
+    name.append_entry_by_nid(Nid::COMMONNAME, "localhost")
+        .unwrap();
+    let name = name.build();
+
+    let mut cert = X509::builder().unwrap();
+    cert.set_version(2).unwrap();
+    cert.set_subject_name(&name).unwrap();
+    cert.set_issuer_name(&name).unwrap();
+    cert.set_pubkey(&key).unwrap();
+    cert.set_not_before(&Asn1Time::days_from_now(0).unwrap())
+        .unwrap();
+    cert.set_not_after(&Asn1Time::days_from_now(1).unwrap())
+        .unwrap();
+    let san = SubjectAlternativeName::new()
+        .dns("localhost")
+        .build(&cert.x509v3_context(None, None))
+        .unwrap();
+    cert.append_extension(san).unwrap();
+    cert.sign(&key, MessageDigest::sha256()).unwrap();
+
+    (
+        CertificateDer::from(cert.build().to_der().unwrap()),
+        PrivatePkcs8KeyDer::from(key.private_key_to_pkcs8().unwrap()).into(),
+    )
+}
+
+/// Moves TLS records between the client and server until neither has anything to send.
+fn transfer(client: &mut ClientConnection, server: &mut ServerConnection) {
+    loop {
+        let mut buf = Vec::new();
+        let mut moved = false;
+        while client.wants_write() {
+            client.write_tls(&mut buf).unwrap();
+            moved = true;
+        }
+        if !buf.is_empty() {
+            server.read_tls(&mut &buf[..]).unwrap();
+            server.process_new_packets().unwrap();
+        }
+
+        let mut buf = Vec::new();
+        while server.wants_write() {
+            server.write_tls(&mut buf).unwrap();
+            moved = true;
+        }
+        if !buf.is_empty() {
+            client.read_tls(&mut &buf[..]).unwrap();
+            client.process_new_packets().unwrap();
+        }
+
+        if !moved {
+            break;
+        }
+    }
+}
+
+#[test]
+fn handshake() {
+    let (cert, key) = self_signed_cert();
+    let mut roots = RootCertStore::empty();
+    roots.add(cert.clone()).unwrap();
+
+    let client_config = make_client_config(roots, vec![MLKEM768]).unwrap();
+    let server_provider =
+        rustls_openssl::custom_provider(rustls_openssl::ALL_CIPHER_SUITES.to_vec(), vec![MLKEM768]);
+    let server_config = ServerConfig::builder_with_provider(Arc::new(server_provider))
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_no_client_auth()
+        .with_single_cert(vec![cert], key)
+        .unwrap();
+
+    let mut client =
+        ClientConnection::new(Arc::new(client_config), "localhost".try_into().unwrap()).unwrap();
+    let mut server = ServerConnection::new(Arc::new(server_config)).unwrap();
+    transfer(&mut client, &mut server);
+
+    assert!(!client.is_handshaking());
+    assert!(!server.is_handshaking());
+    assert_eq!(
+        client.negotiated_key_exchange_group().unwrap().name(),
+        MLKEM768.name()
+    );
+}
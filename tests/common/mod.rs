@@ -0,0 +1,28 @@
+//! Helpers shared by the test vector tests.
+use foreign_types::ForeignType;
+use openssl::pkey::{PKey, Private};
+use std::ffi::CStr;
+use std::ptr;
+
+/// Decodes a hex string.
+pub fn hex(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+/// Loads a raw private key, e.g an ML-KEM decapsulation key, for an algorithm such as `mlkem768`.
+pub fn private_key_from_raw(dk: &[u8], algorithm: &CStr) -> PKey<Private> {
+    unsafe {
+        let pkey = openssl_sys::EVP_PKEY_new_raw_private_key_ex(
+            ptr::null_mut(),
+            algorithm.as_ptr(),
+            ptr::null(),
+            dk.as_ptr(),
+            dk.len(),
+        );
+        assert!(!pkey.is_null(), "{}", openssl::error::ErrorStack::get());
+        PKey::from_ptr(pkey)
+    }
+}
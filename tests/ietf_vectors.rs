@@ -0,0 +1,261 @@
+//! Test vectors for the X25519MLKEM768 key exchange from [draft-kwiatkowski-tls-ecdhe-mlkem].
+//!
+//! The draft doesn't publish test vectors of its own, so the vector here is composed from the ML-KEM-768 NIST ACVP
+//! vector with tcId 26 (see `tests/kat.rs`) and the X25519 vectors from [RFC 7748 section 6.1], in the order the
+//! draft specifies: the ML-KEM component comes first in both key shares and in the shared secret.
+//!
+//! oqsprovider doesn't let the key pairs or encapsulation randomness be chosen, so our client and server can't
+//! reproduce the key shares. Instead each side is checked against a peer built from the vector's component keys,
+//! which catches the components being swapped.
+//!
+//! [draft-kwiatkowski-tls-ecdhe-mlkem]: https://datatracker.ietf.org/doc/draft-kwiatkowski-tls-ecdhe-mlkem/
+//! [RFC 7748 section 6.1]: https://www.rfc-editor.org/rfc/rfc7748#section-6.1
+mod common;
+
+use common::{hex, private_key_from_raw};
+use openssl::derive::Deriver;
+use openssl::pkey::{Id, PKey};
+use rustls::crypto::{ActiveKeyExchange, SharedSecret, SupportedKxGroup};
+use rustls::NamedGroup;
+use rustls_liboqs::{
+    split_hybrid_pub_key, split_hybrid_secret, KeyExchange, KxGroup, OqsProviderGuard,
+    X25519MLKEM768,
+};
+
+/// The ML-KEM-768 encapsulation key.
+const MLKEM768_EK: &str = concat!(
+    "89d2cb65f94dcbfc890efc7d0e5a7a38344d1641a3d0b024d50797a5f23c3a18b3101a1269069f43a842bacc098a8821",
+    "271c673db1beb33034e4d7774d16635c7c2c3c2763453538bc1632e1851591a51642974e5928abb8e55fe55612f9b141",
+    "aff015545394b2092e590970ec29a7b7e7aa1fb4493bf7cb731906c2a5cb49e6614859064e19b8fa26af51c44b5e7535",
+    "bfdac072b646d3ea490d277f0d97ced47395fed91e8f2bce0e3ca122c2025f74067ab928a822b35653a74f06757629af",
+    "b1a1caf237100ea935e793c8f58a71b3d6ae2c8658b10150d4a38f572a0d49d28ae89451d338326fdb3b4350036c1081",
+    "117740edb86b12081c5c1223dbb5660d5b3cb3787d481849304c68be875466f14ee5495c2bd795ae412d09002d65b871",
+    "9b90cba3603ac4958ea03cc138c86f7851593125334701b677f82f4952a4c93b5b4c134bb42a857fd15c650864a6aa94",
+    "eb691c0b691be4684c1f5b7490467fc01b1d1fda4dda35c4ecc231bc73a6fef42c99d34eb82a4d014987b3e386910c62",
+    "679a118f3c5bd9f467e4162042424357db92ef484a4a1798c1257e870a30cb20aaa0335d83314fe0aa7e63a862648041",
+    "a72a6321523220b1ace9bb701b21ac1253cb812c15575a9085eabeade73a4ae76e6a7b158a20586d78a5ac620a5c9abc",
+    "c9c043350a73656b0abe822da5e0ba76045fad75401d7a3b703791b7e99261710f86b72421d240a347638377205a152c",
+    "794130a4e047742b888303bddc309116764de7424cebea6db65348ac537e01a9cc56ea667d5aa87ac9aaa4317d262c10",
+    "143050b8d07a728ca633c13e468abcead372c77b8ecf3b986b98c1e55860b2b4216766ad874c35ed7205068739230220",
+    "b5a2317d102c598356f168acbe80608de4c9a710b8dd07078cd7c671058af1b0b8304a314f7b29be78a933c7b9294424",
+    "954a1bf8bc745de86198659e0e1225a910726074969c39a97c19240601a46e013dcdcb677a8cbd2c95a40629c256f24a",
+    "328951df57502ab30772cc7e5b850027c8551781ce4985bdacf6b865c104e8a4bc65c41694d456b7169e45ab3d7acabe",
+    "afe23ad6a7b94d1979a2f4c1cae7cd77d681d290b5d8e451bfdcccf5310b9d12a88ec29b10255d5e17a192670aa9731c",
+    "5ca67ec784c502781be8527d6fc003c6701b3632284b40307a527c7620377feb0b73f722c9e3cd4dec64876b93ab5b7c",
+    "fc4a657f852b659282864384f442b22e8a21109387b8b47585fc680d0ba45c7a8b1d7274bda57845d100d0f42a3b7462",
+    "8773351fd7ac305b2497639be90b3f4f71a6aa3561eecc6a691bb5cb3914d8634ca1e1af543c049a8c6e868c51f0423b",
+    "d2d5ae09b79e57c27f3fe3ae2b26a441babfc6718ce8c05b4fe793b910b8fbcbbe7f1013242b40e0514d0bdc5c88bac5",
+    "94c794ce5122fbf34896819147b928381587963b0b90034aa07a10be176e01c80ad6a4b71b10af4241400a2a4cbbc059",
+    "61a15ec1474ed51a3cc6d35800679a462809caa3ab4f7094cd6610b4a700cba939e7eac93e38c99755908727619ed76a",
+    "34e53c4fa25bfc97008206697dd145e5b9188e5b014e941681e15fe3e132b8a3903474148ba28b987111c9bcb3989bbb",
+    "c671c581b44a492845f288e62196e471fed3c39c1bbddb0837d0d4706b0922c4",
+);
+
+/// The ML-KEM-768 decapsulation key.
+const MLKEM768_DK: &str = concat!(
+    "b09125afb3cfb5295581373ab6885284d9706318280d223edc987fd14410dbe82e6ac89adfab70e67ca4b1c641ad037f",
+    "d8c47870f159ec79cdcd52605b9890499bb6dbd8347f342c61436b642c0ddf4617db06198b8285dce4c09d9775a2f41c",
+    "8cd18af8e75f57d4127df94d901ac83bacbd584cc50c43750f49b357f59350875c9b475480a8aaa168592ddb158614a6",
+    "39813566d205368c6c39f0413ca3230df60d44008282b682ac66b76c3c95f00b2a555035529c86ef3905b4a3968fea78",
+    "02b6c5eecb08e8f0c42d7ab7cd21a62fb136412a1840b52c99970ccf51892f73497c3775be2189f7fc25e7c74d81fc21",
+    "7683292aa4866ddb04469855323a0810f0893de5c7f94a9c0b5337db83c44891b2e694695b76575032bf51761682958b",
+    "d4f97be9a355b4a85bb6858b7e5a5ef653ab781056af9187d811c3a8936e5706503db57062410bcc9421f1ab867a6578",
+    "56c411c4e025ecb3c387729ae8e112f330b988e22f47c35c280750d21b107687af7b329ef3cb5289f06fb7d44548391e",
+    "97ba6dd499b5907c54958413d92aa99d5646cf47a8f48cb70a07ad056b4eefe6c8c46645f7028a32410558638c48e83a",
+    "c1570160c3833bf64052f5b7df4364d3e0b24e790aa7c98cee0441e6731d9de22d156c61e1c740397672ef54724f01b9",
+    "d49923aa321f86b98823f21360138392b90c69434635275f9bfbb9b8a99e8e1b7f4ec25f75dbce33c13f750170bd6722",
+    "efe496e7463e16aaa5867b869a96ad41b22bd2556c924596fd778d79a102f6e46d8eb18fefac8db19993e5414ac81670",
+    "5286892492c8c9e852d6145dff0c10e4a6703a459e7e732a6dfa2766a622b0622bfedb8f41c125f61b2ec264853b9ccc",
+    "165979f6a263beb148905aac7618a70e829e23f28696f92ef6fa07c102cdbdb1288ba5cff3a81abba15974535fe3106a",
+    "80068f14e98964572350a7112b1601c196710c096ccf164fbce1aabac9c5b9535070e61ab8068d611ca765fabb641260",
+    "7dab30c4fc6ad073731fdc4c48b88e267c47b439ad2560c30561815ceb1f52c896489944bbbab52b1b1d1680a1057964",
+    "dafa600c93a39a447ddbb0adf911afe3e823d8acc7cc04659f625f2c1837bb175282542cd22601f621581ab5a6c0384e",
+    "087ccd32a5380b522fdd3a4202b5b41c85caff2903b2dc2645703d9bc711fbb404c0c0376187ac588aaf5718522d2273",
+    "a9408dabcbc9701698d2da172aa6267a4c9693a24011c2265a2b6dc8e96304a98ddc5319a3140c399a08412c20f48537",
+    "870bb84c32a094457895511ff7ec421de01a64b78534653f78327441b90cd115939dfaafa95b40d0a63d62d12eb5c909",
+    "6018cc83871e44e6cd0be26d16b7b5a209b8e6471d2954adf9fabd0153707c9caa2bcc38ded841c791a0eb597eeee2c5",
+    "18d926edb28ab53caa5b7746466931b0ac9150688bf37049c1f82bcf648332434cd0a92fd2c958353a26cb65cb499057",
+    "109b2d688cc43c4b385da7c50868af1b8075e57088f5db12dfa493eacb6dc4ec6e205baa2a89858ec2823c00553714cd",
+    "e47a96e36c7c198b3ec57ccf74d92cddb86aa0a8b8b5ca9d52bb60aba79f4f72b0125532ceb7a9077480d2bb60df51a9",
+    "89d2cb65f94dcbfc890efc7d0e5a7a38344d1641a3d0b024d50797a5f23c3a18b3101a1269069f43a842bacc098a8821",
+    "271c673db1beb33034e4d7774d16635c7c2c3c2763453538bc1632e1851591a51642974e5928abb8e55fe55612f9b141",
+    "aff015545394b2092e590970ec29a7b7e7aa1fb4493bf7cb731906c2a5cb49e6614859064e19b8fa26af51c44b5e7535",
+    "bfdac072b646d3ea490d277f0d97ced47395fed91e8f2bce0e3ca122c2025f74067ab928a822b35653a74f06757629af",
+    "b1a1caf237100ea935e793c8f58a71b3d6ae2c8658b10150d4a38f572a0d49d28ae89451d338326fdb3b4350036c1081",
+    "117740edb86b12081c5c1223dbb5660d5b3cb3787d481849304c68be875466f14ee5495c2bd795ae412d09002d65b871",
+    "9b90cba3603ac4958ea03cc138c86f7851593125334701b677f82f4952a4c93b5b4c134bb42a857fd15c650864a6aa94",
+    "eb691c0b691be4684c1f5b7490467fc01b1d1fda4dda35c4ecc231bc73a6fef42c99d34eb82a4d014987b3e386910c62",
+    "679a118f3c5bd9f467e4162042424357db92ef484a4a1798c1257e870a30cb20aaa0335d83314fe0aa7e63a862648041",
+    "a72a6321523220b1ace9bb701b21ac1253cb812c15575a9085eabeade73a4ae76e6a7b158a20586d78a5ac620a5c9abc",
+    "c9c043350a73656b0abe822da5e0ba76045fad75401d7a3b703791b7e99261710f86b72421d240a347638377205a152c",
+    "794130a4e047742b888303bddc309116764de7424cebea6db65348ac537e01a9cc56ea667d5aa87ac9aaa4317d262c10",
+    "143050b8d07a728ca633c13e468abcead372c77b8ecf3b986b98c1e55860b2b4216766ad874c35ed7205068739230220",
+    "b5a2317d102c598356f168acbe80608de4c9a710b8dd07078cd7c671058af1b0b8304a314f7b29be78a933c7b9294424",
+    "954a1bf8bc745de86198659e0e1225a910726074969c39a97c19240601a46e013dcdcb677a8cbd2c95a40629c256f24a",
+    "328951df57502ab30772cc7e5b850027c8551781ce4985bdacf6b865c104e8a4bc65c41694d456b7169e45ab3d7acabe",
+    "afe23ad6a7b94d1979a2f4c1cae7cd77d681d290b5d8e451bfdcccf5310b9d12a88ec29b10255d5e17a192670aa9731c",
+    "5ca67ec784c502781be8527d6fc003c6701b3632284b40307a527c7620377feb0b73f722c9e3cd4dec64876b93ab5b7c",
+    "fc4a657f852b659282864384f442b22e8a21109387b8b47585fc680d0ba45c7a8b1d7274bda57845d100d0f42a3b7462",
+    "8773351fd7ac305b2497639be90b3f4f71a6aa3561eecc6a691bb5cb3914d8634ca1e1af543c049a8c6e868c51f0423b",
+    "d2d5ae09b79e57c27f3fe3ae2b26a441babfc6718ce8c05b4fe793b910b8fbcbbe7f1013242b40e0514d0bdc5c88bac5",
+    "94c794ce5122fbf34896819147b928381587963b0b90034aa07a10be176e01c80ad6a4b71b10af4241400a2a4cbbc059",
+    "61a15ec1474ed51a3cc6d35800679a462809caa3ab4f7094cd6610b4a700cba939e7eac93e38c99755908727619ed76a",
+    "34e53c4fa25bfc97008206697dd145e5b9188e5b014e941681e15fe3e132b8a3903474148ba28b987111c9bcb3989bbb",
+    "c671c581b44a492845f288e62196e471fed3c39c1bbddb0837d0d4706b0922c472e31df613da9a1dd33b5d2d8939684b",
+    "89f7649e1c59b959ffbe972786c477f66177dbf3b059173fd06afcd90e80e862174fc57f97607bbff5b73d6360fb5c37",
+);
+
+/// The ML-KEM-768 ciphertext.
+const MLKEM768_C: &str = concat!(
+    "56b42d593aab8e8773bd92d76eabddf3b1546f8326f57a7b773764b6c0dd30470f68dff82e0dca92509274ecfe83a954",
+    "735fde6e14676daaa3680c30d524f4efa79ed6a1f9ed7e1c00560e8683538c3105ab931be0d2b249b38cb9b13af5ceaf",
+    "7887a59dba16688a7f28de0b14d19f391eb41832a56479416ccf94e997390ed7878eeaff49328a70e0ab5fce6c63c09b",
+    "35f4e45994de615b88bb722f70e87d2bbd72ae71e1ee9008e459d8e743039a8ddeb874fce5301a2f8c0ee8c2fee7a4ee",
+    "68b5ed6a6d9ab74f98bb3ba0fe89e82bd5a525c5e8790f818ccc605877d46c8bdb5c337b025bb840ff471896e43bfa99",
+    "d73dbe31805c27a43e57f0618b3ae522a4644e0d4e4c1c548489431be558f3bfc50e16617e110dd7af9a6fd83e3fbb68",
+    "c304d15f6cb700d61d7aa915a6751ea3ba80223e654132a20999a43bf408592730b9a9499636c09fa729f9cb1f9d3442",
+    "f47357a2b9cf15d3103b9bf396c23088f118ede346b5c03891cfa5d517cef8471322e7e31087c4b036abad784bff72a9",
+    "b11fa198facbcb91f067feaf76fcfe5327c1070b3da6988400756760d2d1f060298f1683d51e3616e98c51c9c03aa42f",
+    "2e633651a47ad3cc2ab4a852ae0c4b04b4e1c3dd944445a2b12b4f42a6435105c04122fc3587afe409a00b308d63c5dd",
+    "8163654504eedbb7b5329577c35fbeb3f463872cac28142b3c12a740ec6ea7ce9ad78c6fc8fe1b4df5fc55c1667f31f2",
+    "312da07799dc870a478608549fedafe021f1cf2984180364e90ad98d845652aa3cdd7a8eb09f5e51423fab42a7b7bb4d",
+    "514864be8d71297e9c3b17a993f0ae62e8ef52637bd1b885bd9b6ab727854d703d8dc478f96cb81fce4c60383ac01fcf",
+    "0f971d4c8f352b7a82e218652f2c106ca92ae686bacfcef5d327347a97a9b375d67341552bc2c538778e0f9801823ccd",
+    "fcd1eaaded55b18c9757e3f212b2889d3857db51f981d16185fd0f900853a75005e3020a8b95b7d8f2f2631c70d78a95",
+    "7c7a62e1b3719070acd1fd480c25b83847da027b6ebbc2eec2df22c87f9b46d5d7baf156b53cee929572b92c4784c4e8",
+    "29f3446a1ffe47f99decd0436029ddebd3ed8e87e5e73d123dbe8a4ddacf2abde87f33ae2b621c0ec5d5cad1259deec2",
+    "aeff6088f04f27a20338b5762543e5100899a4cbfb7b3ca456b3a19b83a4c432230c23e1c7f107c4cb112152f1c0f30d",
+    "a0bb33f4f11f47eea43872bafa84ae22256d708e0604dade4b2a4dde8cccf11930e13553934ae3ece52f3d7ccc002873",
+    "77879fe6b8ece7ef79423507c9da339559c20de1c51955999bae47401dc3cdfaa1b256d09c7db9fc8698bfcefa7302d5",
+    "6fbcde1fbaaa1c653454e6fd3d84e4f79a931c681cbb6cb462b10dae112bdfb7f65c7fdf6e5fc594ec3a474a94bd97e6",
+    "ec81f71c230bf70ca0f13ce3dffbd9ff9804efd8f37a4d3629b43a8f55544ebc5ac0abd9a33d79699068346a0f1a3a96",
+    "e115a5d80be165b562d082984d5aacc3a2301981a6418f8ba7d7b0d7ca5875c6",
+);
+
+/// The ML-KEM-768 shared secret.
+const MLKEM768_K: &str = "2696d28e9c61c2a01ce9b1608dcb9d292785a0cd58efb7fe13b1de95f0db55b3";
+
+/// The client's X25519 private key, Alice's from RFC 7748.
+const X25519_CLIENT_PRIVATE: &str =
+    "77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a";
+
+/// The client's X25519 public key.
+const X25519_CLIENT_PUBLIC: &str =
+    "8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a";
+
+/// The server's X25519 private key, Bob's from RFC 7748.
+const X25519_SERVER_PRIVATE: &str =
+    "5dab087e624a8a4b79e17f8b83800ee66f3bb1292618b6fd1c2f8b27ff88e0eb";
+
+/// The server's X25519 public key.
+const X25519_SERVER_PUBLIC: &str =
+    "de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f";
+
+/// The X25519 shared secret.
+const X25519_SHARED_SECRET: &str =
+    "4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742";
+
+const MLKEM768_EK_LEN: usize = 1184;
+const MLKEM768_C_LEN: usize = 1088;
+
+/// The client key share, `ek || X25519 public key`.
+fn client_key_share() -> Vec<u8> {
+    [hex(MLKEM768_EK), hex(X25519_CLIENT_PUBLIC)].concat()
+}
+
+/// The server key share, `c || X25519 public key`.
+fn server_key_share() -> Vec<u8> {
+    [hex(MLKEM768_C), hex(X25519_SERVER_PUBLIC)].concat()
+}
+
+/// The shared secret, `K || X25519 shared secret`.
+fn shared_secret() -> Vec<u8> {
+    [hex(MLKEM768_K), hex(X25519_SHARED_SECRET)].concat()
+}
+
+/// Returns the client's ML-KEM-768 key pair as a key exchange.
+fn client_mlkem768() -> Box<KeyExchange> {
+    let der = private_key_from_raw(&hex(MLKEM768_DK), c"mlkem768")
+        .private_key_to_der()
+        .unwrap();
+    KeyExchange::from_raw_parts(hex(MLKEM768_EK), der, KxGroup::MLKEM768).unwrap()
+}
+
+/// Performs an X25519 key agreement.
+fn x25519(private_key: &str, peer_public_key: &[u8]) -> Vec<u8> {
+    let private_key = PKey::private_key_from_raw_bytes(&hex(private_key), Id::X25519).unwrap();
+    let peer_public_key = PKey::public_key_from_raw_bytes(peer_public_key, Id::X25519).unwrap();
+    let mut deriver = Deriver::new(&private_key).unwrap();
+    deriver.set_peer(&peer_public_key).unwrap();
+    deriver.derive_to_vec().unwrap()
+}
+
+#[test]
+fn vector_is_consistent() {
+    let _providers = OqsProviderGuard::load().unwrap();
+
+    // Decapsulate the server key share with the client's component keys
+    let server_key_share = server_key_share();
+    let (c, x25519_public) = server_key_share.split_at(MLKEM768_C_LEN);
+    let mlkem_secret = client_mlkem768().complete(c).unwrap();
+    let x25519_secret = x25519(X25519_CLIENT_PRIVATE, x25519_public);
+
+    assert_eq!(
+        [mlkem_secret.secret_bytes(), &x25519_secret].concat(),
+        shared_secret()
+    );
+    let shared_secret = SharedSecret::from(shared_secret());
+    assert_eq!(
+        split_hybrid_secret(&shared_secret, NamedGroup::X25519MLKEM768),
+        Some((x25519_secret.as_slice(), mlkem_secret.secret_bytes()))
+    );
+    assert_eq!(
+        &client_key_share()[MLKEM768_EK_LEN..],
+        PKey::private_key_from_raw_bytes(&hex(X25519_CLIENT_PRIVATE), Id::X25519)
+            .unwrap()
+            .raw_public_key()
+            .unwrap()
+    );
+}
+
+#[test]
+fn server_key_share_order() {
+    let _providers = OqsProviderGuard::load().unwrap();
+
+    let completed = X25519MLKEM768
+        .start_and_complete(&client_key_share())
+        .unwrap();
+    assert_eq!(completed.pub_key.len(), MLKEM768_C_LEN + 32);
+
+    // The client decapsulates the ML-KEM ciphertext first, then the X25519 public key
+    let (c, x25519_public) = completed.pub_key.split_at(MLKEM768_C_LEN);
+    let mlkem_secret = client_mlkem768().complete(c).unwrap();
+    let x25519_secret = x25519(X25519_CLIENT_PRIVATE, x25519_public);
+
+    assert_eq!(
+        completed.secret.secret_bytes(),
+        [mlkem_secret.secret_bytes(), &x25519_secret].concat()
+    );
+}
+
+#[test]
+fn client_key_share_order() {
+    let _providers = OqsProviderGuard::load().unwrap();
+
+    let client = X25519MLKEM768.start().unwrap();
+    assert_eq!(client.pub_key().len(), MLKEM768_EK_LEN + 32);
+
+    // The server encapsulates to the ML-KEM encapsulation key first, then the X25519 public key
+    let (x25519_public, ek) =
+        split_hybrid_pub_key(client.pub_key(), NamedGroup::X25519MLKEM768).unwrap();
+    assert_eq!(ek.len(), MLKEM768_EK_LEN);
+    let mlkem = KxGroup::MLKEM768.start_and_complete(ek).unwrap();
+    let x25519_secret = x25519(X25519_SERVER_PRIVATE, x25519_public);
+    let server_key_share = [mlkem.pub_key.as_slice(), &hex(X25519_SERVER_PUBLIC)].concat();
+
+    let secret = client.complete(&server_key_share).unwrap();
+    assert_eq!(
+        secret.secret_bytes(),
+        [mlkem.secret.secret_bytes(), &x25519_secret].concat()
+    );
+}
@@ -0,0 +1,127 @@
+//! Known answer tests using NIST ACVP ML-KEM test vectors.
+//!
+//! The vectors in `test-data` are the test groups for each parameter set from the `internalProjection.json` sample
+//! file of the NIST ACVP server's [ML-KEM encapsulation/decapsulation test vectors].
+//!
+//! oqsprovider doesn't let the encapsulation randomness be chosen, so encapsulation can't reproduce the known
+//! ciphertexts. Instead the known decapsulation keys are used to check that encapsulating to the known public keys
+//! produces ciphertexts that decapsulate to the same shared secret.
+//!
+//! [ML-KEM encapsulation/decapsulation test vectors]: https://github.com/usnistgov/ACVP-Server/tree/master/gen-val/json-files/ML-KEM-encapDecap-FIPS203
+mod common;
+
+use common::private_key_from_raw;
+use rustls::crypto::{ActiveKeyExchange, SupportedKxGroup};
+use rustls_liboqs::{KeyExchange, KxGroup, OqsProviderGuard};
+use serde_json::Value;
+use std::ffi::CStr;
+
+/// A test case with a known key pair, ciphertext and shared secret.
+struct Vector {
+    tc_id: u64,
+    ek: Vec<u8>,
+    dk: Vec<u8>,
+    c: Vec<u8>,
+    k: Vec<u8>,
+}
+
+fn hex(value: &Value) -> Option<Vec<u8>> {
+    value.as_str().map(common::hex)
+}
+
+/// Reads the vectors for a parameter set, e.g `ML-KEM-768`.
+fn vectors(parameter_set: &str) -> Vec<Vector> {
+    let path = format!(
+        "{}/test-data/{parameter_set}-encapDecap-FIPS203.json",
+        env!("CARGO_MANIFEST_DIR")
+    );
+    let file: Value = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+
+    let mut vectors = Vec::new();
+    for group in file["testGroups"].as_array().unwrap() {
+        assert_eq!(group["parameterSet"], parameter_set);
+        for test in group["tests"].as_array().unwrap() {
+            // Decapsulation groups have a single key pair, encapsulation groups have one per test.
+            vectors.push(Vector {
+                tc_id: test["tcId"].as_u64().unwrap(),
+                ek: hex(&test["ek"]).or_else(|| hex(&group["ek"])).unwrap(),
+                dk: hex(&test["dk"]).or_else(|| hex(&group["dk"])).unwrap(),
+                c: hex(&test["c"]).unwrap(),
+                k: hex(&test["k"]).unwrap(),
+            });
+        }
+    }
+    assert!(vectors.len() >= 5);
+    vectors
+}
+
+/// Loads the known key pair into a key exchange.
+fn key_exchange(vector: &Vector, group: KxGroup, algorithm: &CStr) -> Box<KeyExchange> {
+    let der = private_key_from_raw(&vector.dk, algorithm)
+        .private_key_to_der()
+        .unwrap();
+    KeyExchange::from_raw_parts(vector.ek.clone(), der, group).unwrap()
+}
+
+fn check_decapsulation(parameter_set: &str, group: KxGroup, algorithm: &CStr) {
+    let _providers = OqsProviderGuard::load().unwrap();
+    for vector in vectors(parameter_set) {
+        let secret = key_exchange(&vector, group, algorithm)
+            .complete(&vector.c)
+            .unwrap();
+        assert_eq!(secret.secret_bytes(), vector.k, "tcId {}", vector.tc_id);
+    }
+}
+
+fn check_encapsulation(parameter_set: &str, group: KxGroup, algorithm: &CStr) {
+    let _providers = OqsProviderGuard::load().unwrap();
+    for vector in vectors(parameter_set) {
+        let completed = group.start_and_complete(&vector.ek).unwrap();
+        assert_eq!(
+            completed.pub_key.len(),
+            vector.c.len(),
+            "tcId {}",
+            vector.tc_id
+        );
+
+        let secret = key_exchange(&vector, group, algorithm)
+            .complete(&completed.pub_key)
+            .unwrap();
+        assert_eq!(
+            secret.secret_bytes(),
+            completed.secret.secret_bytes(),
+            "tcId {}",
+            vector.tc_id
+        );
+    }
+}
+
+#[test]
+fn mlkem512_decapsulation() {
+    check_decapsulation("ML-KEM-512", KxGroup::MLKEM512, c"mlkem512");
+}
+
+#[test]
+fn mlkem768_decapsulation() {
+    check_decapsulation("ML-KEM-768", KxGroup::MLKEM768, c"mlkem768");
+}
+
+#[test]
+fn mlkem1024_decapsulation() {
+    check_decapsulation("ML-KEM-1024", KxGroup::MLKEM1024, c"mlkem1024");
+}
+
+#[test]
+fn mlkem512_encapsulation() {
+    check_encapsulation("ML-KEM-512", KxGroup::MLKEM512, c"mlkem512");
+}
+
+#[test]
+fn mlkem768_encapsulation() {
+    check_encapsulation("ML-KEM-768", KxGroup::MLKEM768, c"mlkem768");
+}
+
+#[test]
+fn mlkem1024_encapsulation() {
+    check_encapsulation("ML-KEM-1024", KxGroup::MLKEM1024, c"mlkem1024");
+}
@@ -0,0 +1,92 @@
+//! Roundtrip and property tests of the key exchanges for every exported group.
+use once_cell::sync::OnceCell;
+use proptest::prelude::*;
+use rustls::crypto::SupportedKxGroup;
+use rustls_liboqs::{KxGroup, OqsProviderGuard, XWING};
+
+/// The X-Wing shared secret is a SHA3-256 digest.
+const XWING_SHARED_SECRET_SIZE: usize = 32;
+
+/// Every exported group, with the size of its shared secrets.
+#[allow(deprecated)]
+fn groups() -> Vec<(&'static dyn SupportedKxGroup, usize)> {
+    let kx_groups: [(&'static dyn SupportedKxGroup, KxGroup); 11] = [
+        (rustls_liboqs::MLKEM512, KxGroup::MLKEM512),
+        (rustls_liboqs::MLKEM768, KxGroup::MLKEM768),
+        (rustls_liboqs::MLKEM1024, KxGroup::MLKEM1024),
+        (rustls_liboqs::P256MLKEM768, KxGroup::P256MLKEM768),
+        (rustls_liboqs::P384MLKEM1024, KxGroup::P384MLKEM1024),
+        (rustls_liboqs::X25519MLKEM768, KxGroup::X25519MLKEM768),
+        (rustls_liboqs::X448MLKEM1024, KxGroup::X448MLKEM1024),
+        (rustls_liboqs::HQC128, KxGroup::HQC128),
+        (rustls_liboqs::BIKEL1, KxGroup::BIKEL1),
+        (rustls_liboqs::FRODO640AES, KxGroup::FRODO640AES),
+        (rustls_liboqs::KYBER768, KxGroup::KYBER768),
+    ];
+    kx_groups
+        .into_iter()
+        .map(|(group, kx_group)| (group, kx_group.shared_secret_size().unwrap()))
+        .chain([(XWING, XWING_SHARED_SECRET_SIZE)])
+        .collect()
+}
+
+fn load_providers() {
+    static INSTANCE: OnceCell<OqsProviderGuard> = OnceCell::new();
+    INSTANCE.get_or_init(|| OqsProviderGuard::load().unwrap());
+}
+
+/// Completes a key exchange with a fresh key pair, returning the shared secret.
+fn exchange(group: &dyn SupportedKxGroup) -> Vec<u8> {
+    let kx = group.start().unwrap();
+    let completed = group.start_and_complete(kx.pub_key()).unwrap();
+    let secret = kx.complete(&completed.pub_key).unwrap();
+    assert_eq!(secret.secret_bytes(), completed.secret.secret_bytes());
+    secret.secret_bytes().to_vec()
+}
+
+#[test]
+fn roundtrip() {
+    load_providers();
+
+    for (group, secret_size) in groups() {
+        let mut secrets: Vec<Vec<u8>> = Vec::new();
+        for _ in 0..3 {
+            let secret = exchange(group);
+            assert_eq!(secret.len(), secret_size, "{:?}", group.name());
+            assert!(secret.iter().any(|&b| b != 0), "{:?}", group.name());
+            assert!(!secrets.contains(&secret), "{:?}", group.name());
+            secrets.push(secret);
+        }
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(16))]
+
+    /// Encapsulating to a public key with a corrupted byte either fails, or produces a secret the key pair doesn't
+    /// agree with.
+    ///
+    /// Only the low 7 bits are flipped, as X25519 ignores the top bit of public keys.
+    #[test]
+    fn corrupted_pub_key(index in any::<prop::sample::Index>(), mask in 1u8..0x80) {
+        load_providers();
+
+        for (group, _) in groups() {
+            let kx = group.start().unwrap();
+            let mut pub_key = kx.pub_key().to_vec();
+            let index = index.index(pub_key.len());
+            pub_key[index] ^= mask;
+
+            if let Ok(completed) = group.start_and_complete(&pub_key) {
+                if let Ok(secret) = kx.complete(&completed.pub_key) {
+                    prop_assert_ne!(
+                        secret.secret_bytes(),
+                        completed.secret.secret_bytes(),
+                        "{:?}",
+                        group.name()
+                    );
+                }
+            }
+        }
+    }
+}